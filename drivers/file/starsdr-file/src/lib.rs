@@ -0,0 +1,464 @@
+use std::{
+    fmt::Display,
+    fs::{read_dir, File},
+    io::{BufReader, BufWriter, ErrorKind},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use starsdr_interface::*;
+
+pub use crate::rx::RxFile;
+pub use crate::tx::TxFile;
+
+mod rx;
+mod tx;
+
+/// Treats every IQ recording in `dir` (`*.sc16`, `*.fc32`, `*.fc64`) as a
+/// single channel device.
+pub struct DriverFile {
+    dir: PathBuf,
+}
+
+impl DriverFile {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl DriverFile {
+    /// `path` inside the driver's directory, with symlinks and `..` resolved.
+    fn resolve(&self, path: &str) -> SDRResult<PathBuf> {
+        let dir = self.dir.canonicalize()?;
+        let joined = dir.join(path);
+        let resolved = match joined.canonicalize() {
+            Ok(p) => Some(p),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                match (joined.parent(), joined.file_name()) {
+                    (Some(parent), Some(name)) => Some(parent.canonicalize()?.join(name)),
+                    _ => None,
+                }
+            }
+            Err(e) => return Err(e.into()),
+        };
+        resolved
+            .filter(|p| p.starts_with(&dir) && *p != dir)
+            .ok_or_else(|| SDRError::Param {
+                key: "path".into(),
+                value: path.into(),
+                msg: format!("outside of {}", dir.display()),
+            })
+    }
+}
+
+impl SDRDriver for DriverFile {
+    const NAME: &'static str = "file";
+    type Item = DeviceFile;
+
+    fn list(&self) -> SDRResult<Vec<DeviceFile>> {
+        let mut paths = vec![];
+        for entry in read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.is_file() && format_from_path(&path).is_some() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        paths.into_iter().map(DeviceFile::new).collect()
+    }
+
+    /// Opens `path=...`, relative to the driver's directory, whether or not
+    /// the file exists yet. Paths leading out of the directory are refused.
+    fn open(&self, args: &DeviceArgs) -> SDRResult<DeviceFile> {
+        if let Some(driver) = args.driver().filter(|d| *d != Self::NAME) {
+            return Err(SDRError::Param {
+                key: "driver".into(),
+                value: driver.into(),
+                msg: format!("expect {}", Self::NAME),
+            });
+        }
+        let path = args.get("path").ok_or_else(|| SDRError::Param {
+            key: "path".into(),
+            value: args.to_string(),
+            msg: "missing".into(),
+        })?;
+        let mut device = DeviceFile::new(self.resolve(path)?)?;
+        device.open()?;
+        Ok(device)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct ChannelParams {
+    pub rate: f64,
+    pub freq: f64,
+    pub gain: f64,
+    pub bandwidth: f64,
+}
+
+impl Default for ChannelParams {
+    fn default() -> Self {
+        Self {
+            rate: 1e6,
+            freq: 0.0,
+            gain: 0.0,
+            bandwidth: 1e6,
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Params {
+    pub open: bool,
+    pub tx: ChannelParams,
+    pub rx: ChannelParams,
+}
+
+/// A recording replayed by [`RxFile`]. [`TxFile`] records to the same path,
+/// but only if no file exists there yet.
+pub struct DeviceFile {
+    path: PathBuf,
+    format: SampleFormat,
+    params: Arc<Mutex<Params>>,
+}
+
+impl DeviceFile {
+    pub fn new(path: impl Into<PathBuf>) -> SDRResult<Self> {
+        let path = path.into();
+        let format = format_from_path(&path).ok_or_else(|| SDRError::Param {
+            key: "path".into(),
+            value: path.display().to_string(),
+            msg: "unknown extension, expect sc16, fc32 or fc64".into(),
+        })?;
+        Ok(Self {
+            path,
+            format,
+            params: Arc::new(Mutex::new(Params::default())),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn format(&self) -> SampleFormat {
+        self.format
+    }
+
+    fn use_params<R, F>(&self, channel: usize, f: F) -> SDRResult<R>
+    where
+        F: FnOnce(&mut Params) -> R,
+    {
+        let mut g = self.params.lock().unwrap();
+        if !g.open {
+            return Err(SDRError::NotOpen);
+        }
+        check_channels(&[channel])?;
+        Ok(f(&mut g))
+    }
+}
+
+fn format_from_path(path: &Path) -> Option<SampleFormat> {
+    SampleFormat::from_name(path.extension()?.to_str()?)
+}
+
+fn check_channels(channels: &[usize]) -> SDRResult<()> {
+    if channels != [0] {
+        return Err(SDRError::Param {
+            key: "channels".into(),
+            value: format!("{:?}", channels),
+            msg: "file devices only have channel 0".into(),
+        });
+    }
+    Ok(())
+}
+
+impl Display for DeviceFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "type=file,path={},format={}",
+            self.path.display(),
+            self.format
+        )
+    }
+}
+
+impl SDRDevice for DeviceFile {
+    fn info(&self) -> DeviceInfo {
+        let name = self.path.file_stem().unwrap_or_default().to_string_lossy();
+        DeviceInfo::new(DriverFile::NAME)
+            .with("type", "file")
+            .with("name", name)
+            .with("path", self.path.display())
+            .with("format", self.format)
+    }
+
+    fn open(&mut self) -> SDRResult<()> {
+        self.params.lock().unwrap().open = true;
+        Ok(())
+    }
+
+    fn tx_channel_count(&self) -> SDRResult<usize> {
+        self.use_params(0, |_| 1)
+    }
+
+    fn rx_channel_count(&self) -> SDRResult<usize> {
+        self.use_params(0, |_| 1)
+    }
+
+    fn set_tx_rate(&self, rate: f64, channel: usize) -> SDRResult<()> {
+        self.use_params(channel, |p| p.tx.rate = rate)
+    }
+
+    fn get_tx_rate(&self, channel: usize) -> SDRResult<f64> {
+        self.use_params(channel, |p| p.tx.rate)
+    }
+
+    fn set_tx_freq(&self, freq: f64, channel: usize) -> SDRResult<()> {
+        self.use_params(channel, |p| p.tx.freq = freq)
+    }
+
+    fn get_tx_freq(&self, channel: usize) -> SDRResult<f64> {
+        self.use_params(channel, |p| p.tx.freq)
+    }
+
+    fn set_tx_gain(&self, gain: f64, channel: usize) -> SDRResult<()> {
+        self.use_params(channel, |p| p.tx.gain = gain)
+    }
+
+    fn get_tx_gain(&self, channel: usize) -> SDRResult<f64> {
+        self.use_params(channel, |p| p.tx.gain)
+    }
+
+    fn set_tx_bandwidth(&self, bw: f64, channel: usize) -> SDRResult<()> {
+        self.use_params(channel, |p| p.tx.bandwidth = bw)
+    }
+
+    fn get_tx_bandwidth(&self, channel: usize) -> SDRResult<f64> {
+        self.use_params(channel, |p| p.tx.bandwidth)
+    }
+
+    fn set_rx_rate(&self, rate: f64, channel: usize) -> SDRResult<()> {
+        self.use_params(channel, |p| p.rx.rate = rate)
+    }
+
+    fn get_rx_rate(&self, channel: usize) -> SDRResult<f64> {
+        self.use_params(channel, |p| p.rx.rate)
+    }
+
+    fn set_rx_freq(&self, freq: f64, channel: usize) -> SDRResult<()> {
+        self.use_params(channel, |p| p.rx.freq = freq)
+    }
+
+    fn get_rx_freq(&self, channel: usize) -> SDRResult<f64> {
+        self.use_params(channel, |p| p.rx.freq)
+    }
+
+    fn set_rx_gain(&self, gain: f64, channel: usize) -> SDRResult<()> {
+        self.use_params(channel, |p| p.rx.gain = gain)
+    }
+
+    fn get_rx_gain(&self, channel: usize) -> SDRResult<f64> {
+        self.use_params(channel, |p| p.rx.gain)
+    }
+
+    fn set_rx_bandwidth(&self, bw: f64, channel: usize) -> SDRResult<()> {
+        self.use_params(channel, |p| p.rx.bandwidth = bw)
+    }
+
+    fn get_rx_bandwidth(&self, channel: usize) -> SDRResult<f64> {
+        self.use_params(channel, |p| p.rx.bandwidth)
+    }
+}
+
+impl<T: Sample> CreateTx<T, TxFile<T>> for DeviceFile {
+    fn tx_stream(&self, channels: &[usize]) -> SDRResult<TxFile<T>> {
+        self.use_params(0, |_| ())?;
+        check_channels(channels)?;
+        let file = File::options()
+            .write(true)
+            .create_new(true)
+            .open(&self.path)
+            .map_err(|e| match e.kind() {
+                ErrorKind::AlreadyExists => SDRError::Param {
+                    key: "path".into(),
+                    value: self.path.display().to_string(),
+                    msg: "exists, refusing to overwrite a recording".into(),
+                },
+                _ => e.into(),
+            })?;
+        Ok(TxFile {
+            writer: Mutex::new(BufWriter::new(file)),
+            format: self.format,
+            sample_num_max: SAMPLE_NUM_MAX,
+            _t: PhantomData,
+        })
+    }
+}
+
+impl<T: Sample> CreateRx<T, RxFile<T>> for DeviceFile {
+    fn rx_stream(&self, channels: &[usize]) -> SDRResult<RxFile<T>> {
+        self.use_params(0, |_| ())?;
+        check_channels(channels)?;
+        let file = File::open(&self.path)?;
+        Ok(RxFile {
+            reader: BufReader::new(file),
+            format: self.format,
+            params: self.params.clone(),
+            sample_num_max: SAMPLE_NUM_MAX,
+            scratch: vec![],
+            start: None,
+            delivered: 0,
+            _t: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    /// Removed again on drop, also when the test fails.
+    struct TempDir(PathBuf);
+
+    impl std::ops::Deref for TempDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn temp_dir(name: &str) -> TempDir {
+        let dir =
+            std::env::temp_dir().join(format!("starsdr-file-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    fn record<T: Sample>(path: PathBuf, data: &[Complex<T>]) {
+        let mut d = DeviceFile::new(path).unwrap();
+        d.open().unwrap();
+        let tx: TxFile<T> = d.tx_stream(&[0]).unwrap();
+        tx.send(data).unwrap();
+    }
+
+    #[test]
+    fn test_list() {
+        let dir = temp_dir("list");
+        record::<f32>(dir.join("b.fc32"), &[]);
+        record::<i16>(dir.join("a.sc16"), &[]);
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let devices = DriverFile::new(&*dir).list().unwrap();
+        let formats: Vec<_> = devices.iter().map(|d| d.format()).collect();
+        assert_eq!(vec![SampleFormat::Sc16, SampleFormat::Fc32], formats);
+    }
+
+    #[test]
+    fn test_open() {
+        let dir = temp_dir("open");
+        let driver = DriverFile::new(&*dir);
+        let d = driver
+            .open(&"path=new.sc16,driver=file".parse().unwrap())
+            .unwrap();
+        assert_eq!(dir.canonicalize().unwrap().join("new.sc16"), d.path());
+        assert_eq!(SampleFormat::Sc16, d.format());
+
+        std::fs::create_dir(dir.join("sub")).unwrap();
+        assert!(driver.open(&"path=sub/../a.fc32".parse().unwrap()).is_ok());
+        for args in [
+            "",
+            "path=new.sc16,driver=uhd",
+            "path=../new.sc16",
+            "path=sub/../../new.sc16",
+            "path=/tmp/new.sc16",
+            "path=..",
+        ] {
+            assert!(
+                matches!(
+                    driver.open(&args.parse().unwrap()),
+                    Err(SDRError::Param { .. })
+                ),
+                "{}",
+                args
+            );
+        }
+    }
+
+    #[test]
+    fn test_record_replay() {
+        let dir = temp_dir("replay");
+        let data: Vec<_> = (0..3000)
+            .map(|i| Complex::new(i as f32, -i as f32))
+            .collect();
+        record(dir.join("iq.fc32"), &data[..SAMPLE_NUM_MAX]);
+
+        let mut d = DriverFile::new(&*dir).list().unwrap().pop().unwrap();
+        d.open().unwrap();
+        d.set_rx_rate(0.0, 0).unwrap();
+        let mut rx: RxFile<f32> = d.rx_stream(&[0]).unwrap();
+        assert_eq!(&data[..SAMPLE_NUM_MAX], rx.recv().unwrap().as_slice());
+        assert!(matches!(rx.recv(), Err(SDRError::EndOfStream)));
+    }
+
+    #[test]
+    fn test_convert() {
+        let dir = temp_dir("convert");
+        record(dir.join("iq.sc16"), &[Complex::new(1.0f64, -1.0)]);
+
+        let mut d = DriverFile::new(&*dir).list().unwrap().pop().unwrap();
+        d.open().unwrap();
+        let mut rx: RxFile<i16> = d.rx_stream(&[0]).unwrap();
+        assert_eq!(vec![Complex::new(i16::MAX, -i16::MAX)], rx.recv().unwrap());
+    }
+
+    #[test]
+    fn test_rate() {
+        let dir = temp_dir("rate");
+        record(dir.join("iq.fc64"), &[Complex::new(0.0f64, 0.0); 1000]);
+
+        let mut d = DriverFile::new(&*dir).list().unwrap().pop().unwrap();
+        d.open().unwrap();
+        d.set_rx_rate(20e3, 0).unwrap();
+        let mut rx: RxFile<f64> = d.rx_stream(&[0]).unwrap();
+        let start = Instant::now();
+        assert_eq!(1000, rx.recv().unwrap().len());
+        assert!(start.elapsed().as_secs_f64() >= 0.05);
+    }
+
+    #[test]
+    fn test_channels() {
+        let dir = temp_dir("channels");
+        let mut d = DeviceFile::new(dir.join("iq.fc32")).unwrap();
+        d.open().unwrap();
+        assert!(matches!(d.get_rx_rate(1), Err(SDRError::Param { .. })));
+        let tx: SDRResult<TxFile<f32>> = d.tx_stream(&[0, 1]);
+        assert!(tx.is_err());
+    }
+
+    #[test]
+    fn test_no_overwrite() {
+        let dir = temp_dir("overwrite");
+        let data = [Complex::new(1.0f32, 2.0); 4];
+        record(dir.join("iq.fc32"), &data);
+
+        let mut d = DriverFile::new(&*dir).list().unwrap().pop().unwrap();
+        d.open().unwrap();
+        let tx: SDRResult<TxFile<f32>> = d.tx_stream(&[0]);
+        assert!(matches!(tx, Err(SDRError::Param { .. })));
+        let mut rx: RxFile<f32> = d.rx_stream(&[0]).unwrap();
+        assert_eq!(&data[..], rx.recv().unwrap().as_slice());
+    }
+}
@@ -0,0 +1,67 @@
+use crate::Params;
+use starsdr_interface::*;
+use std::fs::File;
+use std::io::BufReader;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub struct RxFile<T: Send> {
+    pub(crate) reader: BufReader<File>,
+    pub(crate) format: SampleFormat,
+    pub(crate) params: Arc<Mutex<Params>>,
+    pub sample_num_max: usize,
+    pub(crate) scratch: Vec<u8>,
+    pub(crate) start: Option<Instant>,
+    pub(crate) delivered: u64,
+    pub(crate) _t: PhantomData<T>,
+}
+
+impl<T: Send> RxFile<T> {
+    /// Hold samples back until the replay clock, running at the current rx
+    /// rate, has reached them. A rate of 0 replays as fast as possible.
+    fn pace(&mut self, samples: usize) {
+        let rate = self.params.lock().unwrap().rx.rate;
+        let start = *self.start.get_or_insert_with(Instant::now);
+        self.delivered += samples as u64;
+        if rate > 0.0 {
+            let due = start + Duration::from_secs_f64(self.delivered as f64 / rate);
+            let now = Instant::now();
+            if due > now {
+                std::thread::sleep(due - now);
+            }
+        }
+    }
+}
+
+impl<T: Sample> Rx<T> for RxFile<T> {
+    fn sample_num_max(&self) -> usize {
+        self.sample_num_max
+    }
+
+    fn channel_count(&self) -> usize {
+        1
+    }
+
+    fn recv_multi_into(&mut self, bufs: &mut [&mut [Complex<T>]]) -> SDRResult<RecvInfo> {
+        check_channel_buffers(1, bufs.len())?;
+        let buf = &mut *bufs[0];
+        let len = buf.len().min(self.sample_num_max);
+        let n = self
+            .format
+            .read(&mut self.reader, &mut self.scratch, &mut buf[..len])?;
+        if n == 0 && len > 0 {
+            return Err(SDRError::EndOfStream);
+        }
+        let rate = self.params.lock().unwrap().rx.rate;
+        let metadata = RxMetadata {
+            time_spec: (rate > 0.0).then(|| TimeSpec::from_samples(self.delivered, rate)),
+            ..Default::default()
+        };
+        self.pace(n);
+        Ok(RecvInfo {
+            samples: n,
+            metadata,
+        })
+    }
+}
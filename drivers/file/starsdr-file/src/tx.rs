@@ -0,0 +1,35 @@
+use starsdr_interface::*;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::marker::PhantomData;
+use std::sync::Mutex;
+
+pub struct TxFile<T: Send> {
+    pub(crate) writer: Mutex<BufWriter<File>>,
+    pub(crate) format: SampleFormat,
+    pub sample_num_max: usize,
+    pub(crate) _t: PhantomData<T>,
+}
+
+impl<T: Sample> Tx<T> for TxFile<T> {
+    fn channel_count(&self) -> usize {
+        1
+    }
+
+    fn send_multi_with(&self, bufs: &[&[Complex<T>]], _md: &TxMetadata) -> SDRResult<usize> {
+        check_channel_buffers(1, bufs.len())?;
+        let v = bufs[0];
+        let data_len = v.len();
+        if data_len > self.sample_num_max {
+            return Err(SDRError::Param {
+                key: "v".into(),
+                value: format!("len()={}", data_len),
+                msg: format!("> max: {}", self.sample_num_max),
+            });
+        }
+        let mut bytes = Vec::with_capacity(data_len * self.format.sample_size());
+        self.format.encode(v, &mut bytes);
+        self.writer.lock().unwrap().write_all(&bytes)?;
+        Ok(data_len)
+    }
+}
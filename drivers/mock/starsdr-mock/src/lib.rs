@@ -0,0 +1,411 @@
+use std::{fmt::Display, marker::PhantomData, sync::Arc, time::Duration};
+
+use loopback::Loopback;
+use starsdr_interface::*;
+
+pub(crate) mod loopback;
+
+pub use crate::rx::RxMock;
+pub use crate::tx::TxMock;
+
+mod rx;
+mod tx;
+
+/// Behaviour of the simulated devices.
+///
+/// Samples sent on TX channel `n` come back on RX channel `n`, `delay`
+/// samples later, with complex gaussian noise of RMS amplitude `noise` and
+/// rotated by the difference between the TX and RX center frequencies.
+#[derive(Clone, Debug)]
+pub struct MockConfig {
+    pub devices: usize,
+    pub channels: usize,
+    pub delay: usize,
+    pub noise: f64,
+    pub sample_num_max: usize,
+    pub timeout: Duration,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self {
+            devices: 1,
+            channels: 2,
+            delay: 0,
+            noise: 0.0,
+            sample_num_max: SAMPLE_NUM_MAX,
+            timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct DriverMock {
+    config: MockConfig,
+}
+
+impl DriverMock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: MockConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl SDRDriver for DriverMock {
+    const NAME: &'static str = "mock";
+    type Item = DeviceMock;
+
+    fn list(&self) -> SDRResult<Vec<DeviceMock>> {
+        Ok((0..self.config.devices)
+            .map(|i| DeviceMock::new(format!("mock{}", i), self.config.clone()))
+            .collect())
+    }
+}
+
+pub struct DeviceMock {
+    name: String,
+    loopback: Arc<Loopback>,
+}
+
+impl DeviceMock {
+    pub fn new(name: impl Into<String>, config: MockConfig) -> Self {
+        let name = name.into();
+        let seed = name.bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x100_0000_01b3)
+        });
+        Self {
+            name,
+            loopback: Arc::new(Loopback::new(config, seed)),
+        }
+    }
+
+    /// Drops the next `n` samples waiting on RX `channel`, like a packet lost
+    /// on the way from the device. The next receive on the channel reports
+    /// `out_of_sequence`.
+    pub fn drop_rx(&self, n: usize, channel: usize) -> SDRResult<()> {
+        self.loopback.use_channel(channel, |c| c.drop_samples(n))
+    }
+}
+
+impl Display for DeviceMock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "type=mock,name={},channels={}",
+            self.name, self.loopback.config.channels
+        )
+    }
+}
+
+impl SDRDevice for DeviceMock {
+    fn info(&self) -> DeviceInfo {
+        DeviceInfo::new(DriverMock::NAME)
+            .with("type", "mock")
+            .with("name", &self.name)
+            .with("serial", &self.name)
+            .with("product", "loopback")
+    }
+
+    fn open(&mut self) -> SDRResult<()> {
+        self.loopback.open();
+        Ok(())
+    }
+
+    fn tx_channel_count(&self) -> SDRResult<usize> {
+        self.use_open(|| self.loopback.config.channels)
+    }
+
+    fn rx_channel_count(&self) -> SDRResult<usize> {
+        self.use_open(|| self.loopback.config.channels)
+    }
+
+    fn set_tx_rate(&self, rate: f64, channel: usize) -> SDRResult<()> {
+        self.loopback.use_channel(channel, |c| c.tx.rate = rate)
+    }
+
+    fn get_tx_rate(&self, channel: usize) -> SDRResult<f64> {
+        self.loopback.use_channel(channel, |c| c.tx.rate)
+    }
+
+    fn set_tx_freq(&self, freq: f64, channel: usize) -> SDRResult<()> {
+        self.loopback.use_channel(channel, |c| c.tx.freq = freq)
+    }
+
+    fn get_tx_freq(&self, channel: usize) -> SDRResult<f64> {
+        self.loopback.use_channel(channel, |c| c.tx.freq)
+    }
+
+    fn set_tx_gain(&self, gain: f64, channel: usize) -> SDRResult<()> {
+        self.loopback.use_channel(channel, |c| c.tx.gain = gain)
+    }
+
+    fn get_tx_gain(&self, channel: usize) -> SDRResult<f64> {
+        self.loopback.use_channel(channel, |c| c.tx.gain)
+    }
+
+    fn set_tx_bandwidth(&self, bw: f64, channel: usize) -> SDRResult<()> {
+        self.loopback.use_channel(channel, |c| c.tx.bandwidth = bw)
+    }
+
+    fn get_tx_bandwidth(&self, channel: usize) -> SDRResult<f64> {
+        self.loopback.use_channel(channel, |c| c.tx.bandwidth)
+    }
+
+    fn set_rx_rate(&self, rate: f64, channel: usize) -> SDRResult<()> {
+        self.loopback.use_channel(channel, |c| c.rx.rate = rate)
+    }
+
+    fn get_rx_rate(&self, channel: usize) -> SDRResult<f64> {
+        self.loopback.use_channel(channel, |c| c.rx.rate)
+    }
+
+    fn set_rx_freq(&self, freq: f64, channel: usize) -> SDRResult<()> {
+        self.loopback.use_channel(channel, |c| c.rx.freq = freq)
+    }
+
+    fn get_rx_freq(&self, channel: usize) -> SDRResult<f64> {
+        self.loopback.use_channel(channel, |c| c.rx.freq)
+    }
+
+    fn set_rx_gain(&self, gain: f64, channel: usize) -> SDRResult<()> {
+        self.loopback.use_channel(channel, |c| c.rx.gain = gain)
+    }
+
+    fn get_rx_gain(&self, channel: usize) -> SDRResult<f64> {
+        self.loopback.use_channel(channel, |c| c.rx.gain)
+    }
+
+    fn set_rx_bandwidth(&self, bw: f64, channel: usize) -> SDRResult<()> {
+        self.loopback.use_channel(channel, |c| c.rx.bandwidth = bw)
+    }
+
+    fn get_rx_bandwidth(&self, channel: usize) -> SDRResult<f64> {
+        self.loopback.use_channel(channel, |c| c.rx.bandwidth)
+    }
+}
+
+impl DeviceMock {
+    fn use_open<R, F>(&self, f: F) -> SDRResult<R>
+    where
+        F: FnOnce() -> R,
+    {
+        if !self.loopback.lock().open {
+            return Err(SDRError::NotOpen);
+        }
+        Ok(f())
+    }
+}
+
+impl<T: Sample> CreateTx<T, TxMock<T>> for DeviceMock {
+    fn tx_stream(&self, channels: &[usize]) -> SDRResult<TxMock<T>> {
+        self.loopback.check_channels(channels)?;
+        Ok(TxMock {
+            loopback: self.loopback.clone(),
+            channels: channels.to_vec(),
+            sample_num_max: self.loopback.config.sample_num_max,
+            _t: PhantomData,
+        })
+    }
+}
+
+impl<T: Sample> CreateRx<T, RxMock<T>> for DeviceMock {
+    fn rx_stream(&self, channels: &[usize]) -> SDRResult<RxMock<T>> {
+        self.loopback.check_channels(channels)?;
+        Ok(RxMock {
+            loopback: self.loopback.clone(),
+            channels: channels.to_vec(),
+            sample_num_max: self.loopback.config.sample_num_max,
+            _t: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_device(config: MockConfig) -> DeviceMock {
+        let mut d = DriverMock::with_config(config)
+            .list()
+            .unwrap()
+            .pop()
+            .unwrap();
+        d.open().unwrap();
+        d
+    }
+
+    #[test]
+    fn test_list_with() {
+        let driver = DriverMock::with_config(MockConfig {
+            devices: 3,
+            ..Default::default()
+        });
+        let found = driver
+            .list_with(&DeviceFilter::new().serial("mock1"))
+            .unwrap();
+        assert_eq!(1, found.len());
+        assert_eq!(Some("mock1".into()), found[0].serial());
+        assert_eq!("mock", found[0].driver_name());
+        assert!(driver
+            .list_with(&DeviceFilter::new().driver("uhd"))
+            .unwrap()
+            .is_empty());
+
+        let d = driver
+            .open(&"name=mock2,master_clock_rate=200e6".parse().unwrap())
+            .unwrap();
+        assert_eq!(2, d.rx_channel_count().unwrap());
+        assert!(matches!(
+            driver.open(&"name=mock3".parse().unwrap()),
+            Err(SDRError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_not_open() {
+        let d = DriverMock::new().list().unwrap().pop().unwrap();
+        assert!(matches!(d.get_rx_rate(0), Err(SDRError::NotOpen)));
+    }
+
+    #[test]
+    fn test_loopback() {
+        let d = open_device(MockConfig::default());
+        let tx: TxMock<f32> = d.tx_stream(&[1]).unwrap();
+        let mut rx: RxMock<f32> = d.rx_stream(&[1]).unwrap();
+
+        let data: Vec<_> = (0..100)
+            .map(|i| Complex::new(i as f32, -i as f32))
+            .collect();
+        assert_eq!(100, tx.send(&data).unwrap());
+        assert_eq!(data, rx.recv().unwrap());
+    }
+
+    #[test]
+    fn test_recv_into() {
+        let d = open_device(MockConfig::default());
+        let tx: TxMock<f32> = d.tx_stream(&[0]).unwrap();
+        let mut rx: RxMock<f32> = d.rx_stream(&[0]).unwrap();
+
+        tx.send(&[Complex::new(1.0, 1.0); 10]).unwrap();
+        let mut buf = [Complex::default(); 4];
+        assert_eq!(4, rx.recv_into(&mut buf).unwrap().samples);
+        assert_eq!([Complex::new(1.0, 1.0); 4], buf);
+        assert_eq!(6, rx.recv().unwrap().len());
+    }
+
+    #[test]
+    fn test_multi_channel() {
+        let d = open_device(MockConfig::default());
+        let tx: TxMock<f32> = d.tx_stream(&[0, 1]).unwrap();
+        let mut rx: RxMock<f32> = d.rx_stream(&[1, 0]).unwrap();
+
+        let a = [Complex::new(1.0, 0.0); 8];
+        let b = [Complex::new(0.0, 1.0); 8];
+        assert!(tx.send(&a).is_err());
+        assert_eq!(8, tx.send_multi(&[&a, &b]).unwrap());
+
+        let r = rx.recv_multi().unwrap();
+        assert_eq!(vec![b.to_vec(), a.to_vec()], r);
+
+        let rx: SDRResult<RxMock<f32>> = d.rx_stream(&[0, 0]);
+        assert!(matches!(rx, Err(SDRError::Param { .. })));
+        let tx: SDRResult<TxMock<f32>> = d.tx_stream(&[1, 1]);
+        assert!(matches!(tx, Err(SDRError::Param { .. })));
+    }
+
+    #[test]
+    fn test_metadata() {
+        let d = open_device(MockConfig::default());
+        d.set_rx_rate(1e3, 0).unwrap();
+        let tx: TxMock<f32> = d.tx_stream(&[0]).unwrap();
+        let mut rx: RxMock<f32> = d.rx_stream(&[0]).unwrap();
+
+        tx.send(&[Complex::default(); 1500]).unwrap();
+        let mut buf = [Complex::default(); 1000];
+        let first = rx.recv_into(&mut buf).unwrap().metadata;
+        assert_eq!(Some(TimeSpec::new(0, 0.0)), first.time_spec);
+        let (_, second) = rx.recv_with_metadata().unwrap();
+        assert_eq!(Some(TimeSpec::new(1, 0.0)), second.time_spec);
+        assert!(!second.out_of_sequence);
+
+        tx.send(&[Complex::default(); 1500]).unwrap();
+        d.drop_rx(500, 0).unwrap();
+        let (buf, third) = rx.recv_with_metadata().unwrap();
+        assert_eq!(1000, buf.len());
+        assert!(third.out_of_sequence);
+        assert_eq!(Some(TimeSpec::new(2, 0.0)), third.time_spec);
+        tx.send(&[Complex::default(); 10]).unwrap();
+        assert!(!rx.recv_with_metadata().unwrap().1.out_of_sequence);
+    }
+
+    #[test]
+    fn test_loopback_i16() {
+        let d = open_device(MockConfig::default());
+        let tx: TxMock<i16> = d.tx_stream(&[0]).unwrap();
+        let mut rx: RxMock<f64> = d.rx_stream(&[0]).unwrap();
+
+        tx.send(&[Complex::new(i16::MAX, i16::MIN + 1)]).unwrap();
+        assert_eq!(vec![Complex::new(1.0, -1.0)], rx.recv().unwrap());
+    }
+
+    #[test]
+    fn test_delay() {
+        let d = open_device(MockConfig {
+            delay: 10,
+            ..Default::default()
+        });
+        let tx: TxMock<f32> = d.tx_stream(&[0]).unwrap();
+        let mut rx: RxMock<f32> = d.rx_stream(&[0]).unwrap();
+
+        tx.send(&[Complex::new(1.0, 0.0)]).unwrap();
+        let r = rx.recv().unwrap();
+        assert_eq!(11, r.len());
+        assert!(r[..10].iter().all(|s| s.norm() == 0.0));
+        assert_eq!(Complex::new(1.0, 0.0), r[10]);
+    }
+
+    #[test]
+    fn test_freq_offset() {
+        let d = open_device(MockConfig::default());
+        d.set_rx_rate(1e6, 0).unwrap();
+        d.set_tx_freq(100e6 + 250e3, 0).unwrap();
+        d.set_rx_freq(100e6, 0).unwrap();
+        let tx: TxMock<f64> = d.tx_stream(&[0]).unwrap();
+        let mut rx: RxMock<f64> = d.rx_stream(&[0]).unwrap();
+
+        tx.send(&[Complex::new(1.0, 0.0); 4]).unwrap();
+        let r = rx.recv().unwrap();
+        let expect = [(1.0, 0.0), (0.0, 1.0), (-1.0, 0.0), (0.0, -1.0)];
+        for (s, (re, im)) in r.iter().zip(expect) {
+            assert!((s - Complex::new(re, im)).norm() < 1e-9, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_noise() {
+        let d = open_device(MockConfig {
+            noise: 0.1,
+            ..Default::default()
+        });
+        let tx: TxMock<f64> = d.tx_stream(&[0]).unwrap();
+        let mut rx: RxMock<f64> = d.rx_stream(&[0]).unwrap();
+
+        tx.send(&vec![Complex::new(0.0, 0.0); 2000]).unwrap();
+        let r = rx.recv().unwrap();
+        let power = r.iter().map(|s| s.norm_sqr()).sum::<f64>() / r.len() as f64;
+        assert!((power - 0.01).abs() < 0.002, "{power}");
+    }
+
+    #[test]
+    fn test_timeout() {
+        let d = open_device(MockConfig {
+            timeout: Duration::from_millis(10),
+            ..Default::default()
+        });
+        let mut rx: RxMock<f32> = d.rx_stream(&[0]).unwrap();
+        assert!(matches!(rx.recv(), Err(SDRError::TimeOut)));
+    }
+}
@@ -0,0 +1,230 @@
+use std::{
+    collections::VecDeque,
+    f64::consts::PI,
+    sync::{Condvar, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use starsdr_interface::*;
+
+use crate::MockConfig;
+
+#[derive(Clone, Copy)]
+pub(crate) struct ChannelParams {
+    pub rate: f64,
+    pub freq: f64,
+    pub gain: f64,
+    pub bandwidth: f64,
+}
+
+impl Default for ChannelParams {
+    fn default() -> Self {
+        Self {
+            rate: 1e6,
+            freq: 0.0,
+            gain: 0.0,
+            bandwidth: 1e6,
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Channel {
+    pub tx: ChannelParams,
+    pub rx: ChannelParams,
+    queue: VecDeque<Complex<f64>>,
+    phase: f64,
+    received: u64,
+    /// Samples were dropped since the last receive.
+    dropped: bool,
+}
+
+impl Channel {
+    /// Loses up to `n` queued samples as if their packet never arrived.
+    pub fn drop_samples(&mut self, n: usize) {
+        let n = n.min(self.queue.len());
+        self.queue.drain(..n);
+        self.received += n as u64;
+        self.dropped = true;
+    }
+}
+
+pub(crate) struct State {
+    pub open: bool,
+    pub channels: Vec<Channel>,
+    rng: u64,
+}
+
+impl State {
+    fn next_u64(&mut self) -> u64 {
+        // xorshift64*
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn next_uniform(&mut self) -> f64 {
+        ((self.next_u64() >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    }
+
+    fn next_noise(&mut self, amplitude: f64) -> Complex<f64> {
+        // Box-Muller, split evenly between I and Q.
+        let r = (-2.0 * self.next_uniform().ln()).sqrt() * amplitude / 2f64.sqrt();
+        let theta = 2.0 * PI * self.next_uniform();
+        Complex::from_polar(r, theta)
+    }
+}
+
+pub(crate) struct Loopback {
+    pub config: MockConfig,
+    state: Mutex<State>,
+    cond: Condvar,
+}
+
+impl Loopback {
+    pub fn new(config: MockConfig, seed: u64) -> Self {
+        let channels = (0..config.channels).map(|_| Channel::default()).collect();
+        Self {
+            state: Mutex::new(State {
+                open: false,
+                channels,
+                rng: seed | 1,
+            }),
+            config,
+            cond: Condvar::new(),
+        }
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    pub fn open(&self) {
+        let mut g = self.lock();
+        if g.open {
+            return;
+        }
+        let delay = self.config.delay;
+        for ch in g.channels.iter_mut() {
+            ch.queue
+                .extend(std::iter::repeat_n(Complex::new(0.0, 0.0), delay));
+        }
+        g.open = true;
+    }
+
+    pub fn use_channel<R, F>(&self, channel: usize, f: F) -> SDRResult<R>
+    where
+        F: FnOnce(&mut Channel) -> R,
+    {
+        let mut g = self.lock();
+        if !g.open {
+            return Err(SDRError::NotOpen);
+        }
+        let count = g.channels.len();
+        match g.channels.get_mut(channel) {
+            Some(ch) => Ok(f(ch)),
+            None => Err(SDRError::Param {
+                key: "channel".into(),
+                value: channel.to_string(),
+                msg: format!(">= channel count: {}", count),
+            }),
+        }
+    }
+
+    pub fn check_channels(&self, channels: &[usize]) -> SDRResult<()> {
+        if channels.is_empty() {
+            return Err(SDRError::Param {
+                key: "channels".into(),
+                value: "[]".into(),
+                msg: "at least one channel required".into(),
+            });
+        }
+        for (i, &c) in channels.iter().enumerate() {
+            self.use_channel(c, |_| ())?;
+            if channels[..i].contains(&c) {
+                return Err(SDRError::Param {
+                    key: "channels".into(),
+                    value: format!("{:?}", channels),
+                    msg: format!("channel {} listed twice", c),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn push<T: Sample>(&self, channels: &[usize], bufs: &[&[Complex<T>]]) {
+        let mut g = self.lock();
+        for (&channel, v) in channels.iter().zip(bufs) {
+            g.channels[channel]
+                .queue
+                .extend(v.iter().map(|s| Complex::new(s.re.to_f64(), s.im.to_f64())));
+        }
+        self.cond.notify_all();
+    }
+
+    /// Receives the same number of samples on every channel, so streams
+    /// stay aligned across channels.
+    pub fn pop<T: Sample>(
+        &self,
+        channels: &[usize],
+        bufs: &mut [&mut [Complex<T>]],
+        timeout: Duration,
+    ) -> SDRResult<RecvInfo> {
+        let g = self.lock();
+        let (mut g, _) = self
+            .cond
+            .wait_timeout_while(g, timeout, |s| {
+                channels.iter().any(|&c| s.channels[c].queue.is_empty())
+            })
+            .unwrap();
+        let n = channels
+            .iter()
+            .zip(bufs.iter())
+            .map(|(&c, b)| g.channels[c].queue.len().min(b.len()))
+            .min()
+            .unwrap_or(0);
+        if n == 0 {
+            return Err(SDRError::TimeOut);
+        }
+
+        let ch = &g.channels[channels[0]];
+        let metadata = RxMetadata {
+            time_spec: (ch.rx.rate > 0.0).then(|| TimeSpec::from_samples(ch.received, ch.rx.rate)),
+            out_of_sequence: channels.iter().any(|&c| g.channels[c].dropped),
+            ..Default::default()
+        };
+
+        let noise = self.config.noise;
+        for (&channel, buf) in channels.iter().zip(bufs.iter_mut()) {
+            let ch = &g.channels[channel];
+            let step = if ch.rx.rate > 0.0 {
+                2.0 * PI * (ch.tx.freq - ch.rx.freq) / ch.rx.rate
+            } else {
+                0.0
+            };
+            for out in buf[..n].iter_mut() {
+                let ch = &mut g.channels[channel];
+                // Every queue holds at least `n` samples as long as channels
+                // are distinct, which `check_channels` ensures.
+                let Some(mut s) = ch.queue.pop_front() else {
+                    break;
+                };
+                if step != 0.0 {
+                    s *= Complex::from_polar(1.0, ch.phase);
+                    ch.phase = (ch.phase + step) % (2.0 * PI);
+                }
+                if noise > 0.0 {
+                    s += g.next_noise(noise);
+                }
+                *out = Complex::new(T::from_f64(s.re), T::from_f64(s.im));
+            }
+            g.channels[channel].received += n as u64;
+            g.channels[channel].dropped = false;
+        }
+        Ok(RecvInfo {
+            samples: n,
+            metadata,
+        })
+    }
+}
@@ -0,0 +1,31 @@
+use crate::loopback::Loopback;
+use starsdr_interface::*;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+pub struct RxMock<T: Send> {
+    pub(crate) loopback: Arc<Loopback>,
+    pub(crate) channels: Vec<usize>,
+    pub sample_num_max: usize,
+    pub(crate) _t: PhantomData<T>,
+}
+
+impl<T: Sample> Rx<T> for RxMock<T> {
+    fn channel_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    fn sample_num_max(&self) -> usize {
+        self.sample_num_max
+    }
+
+    fn recv_multi_into(&mut self, bufs: &mut [&mut [Complex<T>]]) -> SDRResult<RecvInfo> {
+        check_channel_buffers(self.channels.len(), bufs.len())?;
+        for buf in bufs.iter_mut() {
+            let len = buf.len().min(self.sample_num_max);
+            *buf = &mut std::mem::take(buf)[..len];
+        }
+        self.loopback
+            .pop(&self.channels, bufs, self.loopback.config.timeout)
+    }
+}
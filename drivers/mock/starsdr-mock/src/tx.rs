@@ -0,0 +1,38 @@
+use crate::loopback::Loopback;
+use starsdr_interface::*;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+pub struct TxMock<T: Send> {
+    pub(crate) loopback: Arc<Loopback>,
+    pub(crate) channels: Vec<usize>,
+    pub sample_num_max: usize,
+    pub(crate) _t: PhantomData<T>,
+}
+
+impl<T: Sample> Tx<T> for TxMock<T> {
+    fn channel_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    fn send_multi_with(&self, bufs: &[&[Complex<T>]], _md: &TxMetadata) -> SDRResult<usize> {
+        check_channel_buffers(self.channels.len(), bufs.len())?;
+        let data_len = bufs[0].len();
+        if bufs.iter().any(|b| b.len() != data_len) {
+            return Err(SDRError::Param {
+                key: "bufs".into(),
+                value: format!("{:?}", bufs.iter().map(|b| b.len()).collect::<Vec<_>>()),
+                msg: "channel buffers differ in length".into(),
+            });
+        }
+        if data_len > self.sample_num_max {
+            return Err(SDRError::Param {
+                key: "v".into(),
+                value: format!("len()={}", data_len),
+                msg: format!("> max: {}", self.sample_num_max),
+            });
+        }
+        self.loopback.push(&self.channels, bufs);
+        Ok(data_len)
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use std::ffi::CString;
+
+use starsdr_interface::*;
+use uhd_sys::*;
+
+use crate::errors::handle_uhd_err;
+use crate::structs::{DboardEepromHandle, MboardEepromHandle};
+use crate::{c_string, read_string, DeviceUHD};
+
+/// Keys looked up when reading a motherboard EEPROM. UHD has no way to list
+/// the keys a board actually stores, and boards only keep a subset of these.
+pub const MBOARD_EEPROM_KEYS: &[&str] = &[
+    "name",
+    "serial",
+    "product",
+    "revision",
+    "revision_compat",
+    "model",
+    "mac-addr",
+    "mac-addr0",
+    "mac-addr1",
+    "ip-addr",
+    "ip-addr0",
+    "ip-addr1",
+    "ip-addr2",
+    "ip-addr3",
+    "subnet",
+    "subnet0",
+    "subnet1",
+    "gateway",
+    "gpsdo",
+];
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MboardEeprom {
+    /// Every key of `MBOARD_EEPROM_KEYS` the board has a value for.
+    pub values: BTreeMap<String, String>,
+}
+
+impl MboardEeprom {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|v| v.as_str())
+    }
+
+    pub fn serial(&self) -> Option<&str> {
+        self.get("serial")
+    }
+
+    pub fn revision(&self) -> Option<&str> {
+        self.get("revision")
+    }
+
+    pub fn product(&self) -> Option<&str> {
+        self.get("product")
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.get("name")
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DboardEeprom {
+    /// Daughterboard ID as hex, e.g. "0x0217".
+    pub id: String,
+    pub serial: String,
+    pub revision: i32,
+}
+
+/// Proof that the caller means to write EEPROM. Bad values can leave a
+/// device unable to boot or to be found, so writes take one of these
+/// instead of being one call away from a typo.
+pub struct EepromUnlock(());
+
+impl EepromUnlock {
+    pub fn i_know_what_i_am_doing() -> Self {
+        Self(())
+    }
+}
+
+impl DeviceUHD {
+    #[allow(non_upper_case_globals)]
+    pub fn mboard_eeprom(&self, mboard: usize) -> SDRResult<MboardEeprom> {
+        self.use_usrp(|h| {
+            let eeprom = MboardEepromHandle::new()?;
+            unsafe { handle_uhd_err(uhd_usrp_get_mboard_eeprom(h, eeprom.0, mboard)) }?;
+            let mut values = BTreeMap::new();
+            for key in MBOARD_EEPROM_KEYS {
+                let k = CString::new(*key).unwrap();
+                // A key the board does not store is a KeyError and reads as
+                // empty, any other error is passed on.
+                let v = read_string(|buf, len| match unsafe { uhd_mboard_eeprom_get_value(eeprom.0, k.as_ptr(), buf, len) } {
+                    uhd_error_UHD_ERROR_KEY => uhd_error_UHD_ERROR_NONE,
+                    r => r,
+                })?;
+                if !v.is_empty() {
+                    values.insert(key.to_string(), v);
+                }
+            }
+            Ok(MboardEeprom { values })
+        })
+    }
+
+    /// Writes only the given keys, the rest of the EEPROM is left alone.
+    pub fn set_mboard_eeprom(&self, values: &BTreeMap<String, String>, mboard: usize, _unlock: EepromUnlock) -> SDRResult<()> {
+        let eeprom = MboardEepromHandle::new()?;
+        for (key, value) in values {
+            let k = c_string("key", key)?;
+            let v = c_string(key, value)?;
+            unsafe { handle_uhd_err(uhd_mboard_eeprom_set_value(eeprom.0, k.as_ptr(), v.as_ptr())) }?;
+        }
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_mboard_eeprom(h, eeprom.0, mboard) }))
+    }
+
+    /// `unit` is "RX", "TX" or "GDB", `slot` the daughterboard slot, e.g. "A".
+    pub fn dboard_eeprom(&self, unit: &str, slot: &str, mboard: usize) -> SDRResult<DboardEeprom> {
+        let unit = c_string("unit", unit)?;
+        let slot = c_string("slot", slot)?;
+        self.use_usrp(|h| {
+            let eeprom = DboardEepromHandle::new()?;
+            let e = eeprom.0;
+            unsafe {
+                handle_uhd_err(uhd_usrp_get_dboard_eeprom(h, e, unit.as_ptr(), slot.as_ptr(), mboard))?;
+                let mut revision = 0;
+                handle_uhd_err(uhd_dboard_eeprom_get_revision(e, &mut revision))?;
+                Ok(DboardEeprom {
+                    id: read_string(|buf, len| uhd_dboard_eeprom_get_id(e, buf, len))?,
+                    serial: read_string(|buf, len| uhd_dboard_eeprom_get_serial(e, buf, len))?,
+                    revision,
+                })
+            }
+        })
+    }
+
+    pub fn set_dboard_eeprom(&self, eeprom: &DboardEeprom, unit: &str, slot: &str, mboard: usize, _unlock: EepromUnlock) -> SDRResult<()> {
+        let unit = c_string("unit", unit)?;
+        let slot = c_string("slot", slot)?;
+        let id = c_string("id", &eeprom.id)?;
+        let serial = c_string("serial", &eeprom.serial)?;
+        let handle = DboardEepromHandle::new()?;
+        let e = handle.0;
+        unsafe {
+            handle_uhd_err(uhd_dboard_eeprom_set_id(e, id.as_ptr()))?;
+            handle_uhd_err(uhd_dboard_eeprom_set_serial(e, serial.as_ptr()))?;
+            handle_uhd_err(uhd_dboard_eeprom_set_revision(e, eeprom.revision))?;
+        }
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_dboard_eeprom(h, e, unit.as_ptr(), slot.as_ptr(), mboard) }))
+    }
+}
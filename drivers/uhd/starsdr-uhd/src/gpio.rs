@@ -0,0 +1,235 @@
+use std::ffi::CString;
+use std::ops::{BitOr, Not};
+
+use starsdr_interface::*;
+use uhd_sys::*;
+
+use crate::errors::handle_uhd_err;
+use crate::{c_string, DeviceUHD};
+
+/// GPIO bank registers, see `uhd::usrp::multi_usrp::set_gpio_attr`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GpioAttr {
+    /// 1 = pin driven by the ATR engine, 0 = manual via `Out`.
+    Ctrl,
+    /// Data direction, 1 = output.
+    Ddr,
+    /// Manual output level.
+    Out,
+    /// ATR level while neither transmitting nor receiving.
+    AtrIdle,
+    /// ATR level while only receiving.
+    AtrRx,
+    /// ATR level while only transmitting.
+    AtrTx,
+    /// ATR level while transmitting and receiving.
+    AtrFullDuplex,
+    /// Current input levels, read only.
+    Readback,
+}
+
+impl GpioAttr {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ctrl => "CTRL",
+            Self::Ddr => "DDR",
+            Self::Out => "OUT",
+            Self::AtrIdle => "ATR_0X",
+            Self::AtrRx => "ATR_RX",
+            Self::AtrTx => "ATR_TX",
+            Self::AtrFullDuplex => "ATR_XX",
+            Self::Readback => "READBACK",
+        }
+    }
+}
+
+/// Set of pins in a bank, bit n = pin n.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PinMask(pub u32);
+
+impl PinMask {
+    pub const NONE: Self = Self(0);
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// Pin `n`, which must be below 32.
+    pub fn pin(n: u8) -> SDRResult<Self> {
+        1u32.checked_shl(n as u32).map(Self).ok_or_else(|| SDRError::Param {
+            key: "pin".into(),
+            value: n.to_string(),
+            msg: "a bank has pins 0..32".into(),
+        })
+    }
+
+    pub fn pins(pins: &[u8]) -> SDRResult<Self> {
+        pins.iter().try_fold(Self::NONE, |m, p| Ok(m | Self::pin(*p)?))
+    }
+
+    pub fn contains(&self, n: u8) -> bool {
+        1u32.checked_shl(n as u32).is_some_and(|bit| self.0 & bit != 0)
+    }
+}
+
+impl BitOr for PinMask {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl Not for PinMask {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
+
+impl From<u32> for PinMask {
+    fn from(v: u32) -> Self {
+        Self(v)
+    }
+}
+
+/// Output levels the ATR engine drives for each radio state. A typical
+/// TX/RX switch with PA and LNA enables:
+///
+/// ```
+/// # use starsdr_uhd::gpio::{AtrState, PinMask};
+/// let (pa, lna) = (PinMask::pin(0)?, PinMask::pin(1)?);
+/// let state = AtrState::default().tx(pa).rx(lna).full_duplex(pa | lna);
+/// assert_eq!(0b11, state.full_duplex);
+/// # Ok::<(), starsdr_interface::SDRError>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AtrState {
+    pub idle: u32,
+    pub rx: u32,
+    pub tx: u32,
+    pub full_duplex: u32,
+}
+
+impl AtrState {
+    pub fn idle(mut self, high: PinMask) -> Self {
+        self.idle = high.0;
+        self
+    }
+
+    pub fn rx(mut self, high: PinMask) -> Self {
+        self.rx = high.0;
+        self
+    }
+
+    pub fn tx(mut self, high: PinMask) -> Self {
+        self.tx = high.0;
+        self
+    }
+
+    pub fn full_duplex(mut self, high: PinMask) -> Self {
+        self.full_duplex = high.0;
+        self
+    }
+}
+
+/// One GPIO bank of a motherboard, e.g. the front panel "FP0".
+pub struct Gpio<'a> {
+    device: &'a DeviceUHD,
+    bank: CString,
+    mboard: usize,
+}
+
+impl DeviceUHD {
+    pub fn gpio_banks(&self, mboard: usize) -> SDRResult<Vec<String>> {
+        self.get_strings(|h, out| unsafe { uhd_usrp_get_gpio_banks(h, mboard, out) })
+    }
+
+    pub fn gpio(&self, bank: &str, mboard: usize) -> SDRResult<Gpio<'_>> {
+        Ok(Gpio {
+            device: self,
+            bank: c_string("bank", bank)?,
+            mboard,
+        })
+    }
+}
+
+impl Gpio<'_> {
+    /// Writes the bits of `value` selected by `mask`, leaving other pins as
+    /// they are.
+    pub fn set_attr(&self, attr: GpioAttr, value: u32, mask: PinMask) -> SDRResult<()> {
+        let name = CString::new(attr.name()).unwrap();
+        self.device.use_usrp(|h| {
+            handle_uhd_err(unsafe {
+                uhd_usrp_set_gpio_attr(h, self.bank.as_ptr(), name.as_ptr(), value, mask.0, self.mboard)
+            })
+        })
+    }
+
+    pub fn get_attr(&self, attr: GpioAttr) -> SDRResult<u32> {
+        let name = CString::new(attr.name()).unwrap();
+        self.device.use_usrp(|h| {
+            let mut value = 0;
+            unsafe {
+                handle_uhd_err(uhd_usrp_get_gpio_attr(h, self.bank.as_ptr(), name.as_ptr(), self.mboard, &mut value))
+            }?;
+            Ok(value)
+        })
+    }
+
+    /// Makes the pins manually driven outputs.
+    pub fn set_output(&self, mask: PinMask) -> SDRResult<()> {
+        self.set_attr(GpioAttr::Ctrl, 0, mask)?;
+        self.set_attr(GpioAttr::Ddr, u32::MAX, mask)
+    }
+
+    pub fn set_input(&self, mask: PinMask) -> SDRResult<()> {
+        self.set_attr(GpioAttr::Ctrl, 0, mask)?;
+        self.set_attr(GpioAttr::Ddr, 0, mask)
+    }
+
+    /// Drives manual outputs: pins in `high` go high, the rest of `mask` low.
+    pub fn write(&self, high: PinMask, mask: PinMask) -> SDRResult<()> {
+        self.set_attr(GpioAttr::Out, high.0, mask)
+    }
+
+    pub fn read(&self) -> SDRResult<u32> {
+        self.get_attr(GpioAttr::Readback)
+    }
+
+    /// Hands the pins to the ATR engine, which then switches them with the
+    /// radio state.
+    pub fn set_atr(&self, mask: PinMask, state: AtrState) -> SDRResult<()> {
+        self.set_attr(GpioAttr::AtrIdle, state.idle, mask)?;
+        self.set_attr(GpioAttr::AtrRx, state.rx, mask)?;
+        self.set_attr(GpioAttr::AtrTx, state.tx, mask)?;
+        self.set_attr(GpioAttr::AtrFullDuplex, state.full_duplex, mask)?;
+        self.set_attr(GpioAttr::Ddr, u32::MAX, mask)?;
+        self.set_attr(GpioAttr::Ctrl, u32::MAX, mask)
+    }
+
+    pub fn atr(&self) -> SDRResult<AtrState> {
+        Ok(AtrState {
+            idle: self.get_attr(GpioAttr::AtrIdle)?,
+            rx: self.get_attr(GpioAttr::AtrRx)?,
+            tx: self.get_attr(GpioAttr::AtrTx)?,
+            full_duplex: self.get_attr(GpioAttr::AtrFullDuplex)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_mask() {
+        let mask = PinMask::pins(&[0, 3, 31]).unwrap();
+        assert_eq!(0x8000_0009, mask.0);
+        assert!(mask.contains(3));
+        assert!(!mask.contains(4));
+        assert!(!mask.contains(32));
+        assert!(!PinMask::ALL.contains(200));
+        assert_eq!(PinMask(!0x8000_0009), !mask);
+        assert!(matches!(PinMask::pin(32), Err(SDRError::Param { .. })));
+        assert!(PinMask::pins(&[1, 40]).is_err());
+    }
+}
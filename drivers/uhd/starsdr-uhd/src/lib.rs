@@ -3,6 +3,7 @@ use errors::handle_uhd_err;
 use log::{info, debug};
 use starsdr_interface::*;
 use std::{
+    cell::RefCell,
     ffi::CString,
     fmt::Display,
     marker::PhantomData,
@@ -20,6 +21,8 @@ pub use crate::tx::TxUHD;
 pub use starsdr_interface::CreateTx;
 use structs::*;
 
+pub mod eeprom;
+pub mod gpio;
 pub mod rx;
 pub mod tx;
 pub struct DriverUHD {}
@@ -30,16 +33,16 @@ impl DriverUHD {
     }
 }
 
-impl SDRDriver for DriverUHD {
-    type Item = DeviceUHD;
-
-    fn list(&self) -> SDRResult<Vec<DeviceUHD>> {
+impl DriverUHD {
+    /// Runs `uhd_usrp_find`, which takes device address hints like
+    /// `serial=...` or `type=b200`.
+    fn find(&self, hints: &str) -> SDRResult<Vec<DeviceUHD>> {
         let mut out = vec![];
 
         unsafe {
             let mut strings_out = UHDStringVector::new();
 
-            let args = CString::new("").unwrap();
+            let args = c_string("args", hints)?;
             let r = uhd_usrp_find(args.as_ptr(), strings_out.as_mut_ptr());
             handle_uhd_err(r)?;
 
@@ -52,6 +55,45 @@ impl SDRDriver for DriverUHD {
     }
 }
 
+impl SDRDriver for DriverUHD {
+    const NAME: &'static str = "uhd";
+    type Item = DeviceUHD;
+
+    fn list(&self) -> SDRResult<Vec<DeviceUHD>> {
+        self.find("")
+    }
+
+    fn list_with(&self, filter: &DeviceFilter) -> SDRResult<Vec<DeviceUHD>> {
+        if filter.values.get("driver").is_some_and(|d| d != Self::NAME) {
+            return Ok(vec![]);
+        }
+        let hints = filter
+            .values
+            .iter()
+            .filter(|(k, _)| *k != "driver")
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join(",");
+        let mut devices = self.find(&hints)?;
+        devices.retain(|d| filter.matches(&d.info()));
+        Ok(devices)
+    }
+
+    /// Opens `args` as a UHD device address without running discovery.
+    fn open(&self, args: &DeviceArgs) -> SDRResult<DeviceUHD> {
+        if let Some(driver) = args.driver().filter(|d| *d != Self::NAME) {
+            return Err(SDRError::Param {
+                key: "driver".into(),
+                value: driver.into(),
+                msg: format!("expect {}", Self::NAME),
+            });
+        }
+        let mut device = DeviceUHD::from(args.driver_args());
+        device.open()?;
+        Ok(device)
+    }
+}
+
 pub struct USRPHandle(uhd_usrp_handle);
 
 unsafe impl Send for USRPHandle {}
@@ -75,7 +117,7 @@ impl From<String> for DeviceUHD {
 }
 
 impl DeviceUHD {
-    fn use_usrp<R, F>(&self, f: F) -> SDRResult<R>
+    pub(crate) fn use_usrp<R, F>(&self, f: F) -> SDRResult<R>
     where
         F: FnOnce(uhd_usrp_handle) -> SDRResult<R>,
     {
@@ -86,6 +128,30 @@ impl DeviceUHD {
         f(g.0)
     }
 
+    /// Runs a UHD range getter and converts its result.
+    fn get_range<F>(&self, f: F) -> SDRResult<MetaRange>
+    where
+        F: FnOnce(uhd_usrp_handle, uhd_meta_range_handle) -> uhd_error,
+    {
+        self.use_usrp(|h| {
+            let range = MetaRangeHandle::new()?;
+            handle_uhd_err(f(h, range.0))?;
+            range.to_meta_range()
+        })
+    }
+
+    /// Runs a UHD getter that fills a string vector.
+    pub(crate) fn get_strings<F>(&self, f: F) -> SDRResult<Vec<String>>
+    where
+        F: FnOnce(uhd_usrp_handle, *mut uhd_string_vector_handle) -> uhd_error,
+    {
+        self.use_usrp(|h| {
+            let mut out = UHDStringVector::new();
+            handle_uhd_err(f(h, out.as_mut_ptr()))?;
+            Ok(out.collect())
+        })
+    }
+
     fn new_tx_streamer<T: Send>(
         &self,
         cpu_fmt: &str,
@@ -108,8 +174,10 @@ impl DeviceUHD {
             })?;
 
             Ok(TxUHD {
-                streamer,
+                streamer: Arc::new(streamer),
                 sample_num_max,
+                channels: channels.len(),
+                md: RefCell::new(None),
                 _t: PhantomData,
             })
         }
@@ -147,6 +215,7 @@ impl DeviceUHD {
                 streamer,
                 sample_num_max,
                 md,
+                channels: channels.len(),
                 _t: PhantomData,
             })
         }
@@ -178,11 +247,16 @@ impl Display for DeviceUHD {
 }
 
 impl SDRDevice for DeviceUHD {
+    fn info(&self) -> DeviceInfo {
+        DeviceInfo::parse(DriverUHD::NAME, &self.args)
+    }
+
     fn open(&mut self) -> SDRResult<()> {
         unsafe {
             let mut g = self.usrp.write().unwrap();
             if g.0.is_null() {
-                handle_uhd_err(uhd_usrp_make(&mut g.0, self.args.as_ptr() as _))?;
+                let args = c_string("args", &self.args)?;
+                handle_uhd_err(uhd_usrp_make(&mut g.0, args.as_ptr()))?;
             }
         }
         Ok(())
@@ -216,12 +290,17 @@ impl SDRDevice for DeviceUHD {
         })
     }
     fn set_tx_freq(&self, freq: f64, channel: usize) -> SDRResult<()> {
+        self.tune_tx(&TuneRequest::new(freq), channel).map(|_| ())
+    }
+
+    fn tune_tx(&self, request: &TuneRequest, channel: usize) -> SDRResult<TuneResult> {
+        let args = c_string("args", &request.args)?;
         self.use_usrp(|h| unsafe {
-            let mut request = new_uhd_tune_request_t(freq);
+            let mut request = new_uhd_tune_request_t(request, &args);
             let mut result = new_uhd_tune_result_t();
-            let r = handle_uhd_err(uhd_usrp_set_tx_freq(h, &mut request, channel, &mut result));
+            handle_uhd_err(uhd_usrp_set_tx_freq(h, &mut request, channel, &mut result))?;
             debug!("result: {:?}", result);
-            r
+            Ok(tune_result(&result))
         })
     }
 
@@ -272,12 +351,17 @@ impl SDRDevice for DeviceUHD {
         })
     }
     fn set_rx_freq(&self, freq: f64, channel: usize) -> SDRResult<()> {
+        self.tune_rx(&TuneRequest::new(freq), channel).map(|_| ())
+    }
+
+    fn tune_rx(&self, request: &TuneRequest, channel: usize) -> SDRResult<TuneResult> {
+        let args = c_string("args", &request.args)?;
         self.use_usrp(|h| unsafe {
-            let mut request = new_uhd_tune_request_t(freq);
+            let mut request = new_uhd_tune_request_t(request, &args);
             let mut result = new_uhd_tune_result_t();
-            let r = handle_uhd_err(uhd_usrp_set_rx_freq(h, &mut request, channel, &mut result));
+            handle_uhd_err(uhd_usrp_set_rx_freq(h, &mut request, channel, &mut result))?;
             debug!("result: {:?}", result);
-            r
+            Ok(tune_result(&result))
         })
     }
 
@@ -315,6 +399,411 @@ impl SDRDevice for DeviceUHD {
             Ok(bw)
         })
     }
+
+    fn tx_rates(&self, channel: usize) -> SDRResult<MetaRange> {
+        self.get_range(|h, r| unsafe { uhd_usrp_get_tx_rates(h, channel, r) })
+    }
+
+    fn tx_freq_range(&self, channel: usize) -> SDRResult<MetaRange> {
+        self.get_range(|h, r| unsafe { uhd_usrp_get_tx_freq_range(h, channel, r) })
+    }
+
+    fn tx_gain_range(&self, channel: usize) -> SDRResult<MetaRange> {
+        let name = CString::new("").unwrap();
+        self.get_range(|h, r| unsafe { uhd_usrp_get_tx_gain_range(h, name.as_ptr(), channel, r) })
+    }
+
+    fn tx_bandwidth_range(&self, channel: usize) -> SDRResult<MetaRange> {
+        self.get_range(|h, r| unsafe { uhd_usrp_get_tx_bandwidth_range(h, channel, r) })
+    }
+
+    fn rx_rates(&self, channel: usize) -> SDRResult<MetaRange> {
+        self.get_range(|h, r| unsafe { uhd_usrp_get_rx_rates(h, channel, r) })
+    }
+
+    fn rx_freq_range(&self, channel: usize) -> SDRResult<MetaRange> {
+        self.get_range(|h, r| unsafe { uhd_usrp_get_rx_freq_range(h, channel, r) })
+    }
+
+    fn rx_gain_range(&self, channel: usize) -> SDRResult<MetaRange> {
+        let name = CString::new("").unwrap();
+        self.get_range(|h, r| unsafe { uhd_usrp_get_rx_gain_range(h, name.as_ptr(), channel, r) })
+    }
+
+    fn rx_bandwidth_range(&self, channel: usize) -> SDRResult<MetaRange> {
+        self.get_range(|h, r| unsafe { uhd_usrp_get_rx_bandwidth_range(h, channel, r) })
+    }
+
+    fn set_tx_antenna(&self, antenna: &str, channel: usize) -> SDRResult<()> {
+        let antenna = c_string("antenna", antenna)?;
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_tx_antenna(h, antenna.as_ptr(), channel) }))
+    }
+
+    fn get_tx_antenna(&self, channel: usize) -> SDRResult<String> {
+        self.use_usrp(|h| read_string(|buf, len| unsafe { uhd_usrp_get_tx_antenna(h, channel, buf, len) }))
+    }
+
+    fn tx_antennas(&self, channel: usize) -> SDRResult<Vec<String>> {
+        self.get_strings(|h, out| unsafe { uhd_usrp_get_tx_antennas(h, channel, out) })
+    }
+
+    fn set_rx_antenna(&self, antenna: &str, channel: usize) -> SDRResult<()> {
+        let antenna = c_string("antenna", antenna)?;
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_rx_antenna(h, antenna.as_ptr(), channel) }))
+    }
+
+    fn get_rx_antenna(&self, channel: usize) -> SDRResult<String> {
+        self.use_usrp(|h| read_string(|buf, len| unsafe { uhd_usrp_get_rx_antenna(h, channel, buf, len) }))
+    }
+
+    fn rx_antennas(&self, channel: usize) -> SDRResult<Vec<String>> {
+        self.get_strings(|h, out| unsafe { uhd_usrp_get_rx_antennas(h, channel, out) })
+    }
+}
+
+impl DeviceTime for DeviceUHD {
+    fn time_now(&self, mboard: usize) -> SDRResult<TimeSpec> {
+        self.use_usrp(|h| {
+            let mut t = TimeSpec::default();
+            unsafe { handle_uhd_err(uhd_usrp_get_time_now(h, mboard, &mut t.full_secs, &mut t.frac_secs)) }?;
+            Ok(t)
+        })
+    }
+
+    fn time_last_pps(&self, mboard: usize) -> SDRResult<TimeSpec> {
+        self.use_usrp(|h| {
+            let mut t = TimeSpec::default();
+            unsafe { handle_uhd_err(uhd_usrp_get_time_last_pps(h, mboard, &mut t.full_secs, &mut t.frac_secs)) }?;
+            Ok(t)
+        })
+    }
+
+    fn set_time_now(&self, time: TimeSpec, mboard: usize) -> SDRResult<()> {
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_time_now(h, time.full_secs, time.frac_secs, mboard) }))
+    }
+
+    fn set_time_next_pps(&self, time: TimeSpec, mboard: usize) -> SDRResult<()> {
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_time_next_pps(h, time.full_secs, time.frac_secs, mboard) }))
+    }
+
+    fn set_time_unknown_pps(&self, time: TimeSpec) -> SDRResult<()> {
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_time_unknown_pps(h, time.full_secs, time.frac_secs) }))
+    }
+
+    fn time_synchronized(&self) -> SDRResult<bool> {
+        self.use_usrp(|h| {
+            let mut synced = false;
+            unsafe { handle_uhd_err(uhd_usrp_get_time_synchronized(h, &mut synced)) }?;
+            Ok(synced)
+        })
+    }
+}
+
+impl GainControl for DeviceUHD {
+    fn tx_gain_names(&self, channel: usize) -> SDRResult<Vec<String>> {
+        self.get_strings(|h, out| unsafe { uhd_usrp_get_tx_gain_names(h, channel, out) })
+    }
+
+    fn set_tx_gain_stage(&self, gain: f64, name: &str, channel: usize) -> SDRResult<()> {
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_tx_gain(h, gain, channel, name.as_ptr()) }))
+    }
+
+    fn get_tx_gain_stage(&self, name: &str, channel: usize) -> SDRResult<f64> {
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| {
+            let mut gain = 0.0;
+            unsafe { handle_uhd_err(uhd_usrp_get_tx_gain(h, channel, name.as_ptr(), &mut gain)) }?;
+            Ok(gain)
+        })
+    }
+
+    fn tx_gain_stage_range(&self, name: &str, channel: usize) -> SDRResult<MetaRange> {
+        let name = c_string("name", name)?;
+        self.get_range(|h, r| unsafe { uhd_usrp_get_tx_gain_range(h, name.as_ptr(), channel, r) })
+    }
+
+    fn set_normalized_tx_gain(&self, gain: f64, channel: usize) -> SDRResult<()> {
+        check_normalized(gain)?;
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_normalized_tx_gain(h, gain, channel) }))
+    }
+
+    fn get_normalized_tx_gain(&self, channel: usize) -> SDRResult<f64> {
+        self.use_usrp(|h| {
+            let mut gain = 0.0;
+            unsafe { handle_uhd_err(uhd_usrp_get_normalized_tx_gain(h, channel, &mut gain)) }?;
+            Ok(gain)
+        })
+    }
+
+    fn rx_gain_names(&self, channel: usize) -> SDRResult<Vec<String>> {
+        self.get_strings(|h, out| unsafe { uhd_usrp_get_rx_gain_names(h, channel, out) })
+    }
+
+    fn set_rx_gain_stage(&self, gain: f64, name: &str, channel: usize) -> SDRResult<()> {
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_rx_gain(h, gain, channel, name.as_ptr()) }))
+    }
+
+    fn get_rx_gain_stage(&self, name: &str, channel: usize) -> SDRResult<f64> {
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| {
+            let mut gain = 0.0;
+            unsafe { handle_uhd_err(uhd_usrp_get_rx_gain(h, channel, name.as_ptr(), &mut gain)) }?;
+            Ok(gain)
+        })
+    }
+
+    fn rx_gain_stage_range(&self, name: &str, channel: usize) -> SDRResult<MetaRange> {
+        let name = c_string("name", name)?;
+        self.get_range(|h, r| unsafe { uhd_usrp_get_rx_gain_range(h, name.as_ptr(), channel, r) })
+    }
+
+    fn set_normalized_rx_gain(&self, gain: f64, channel: usize) -> SDRResult<()> {
+        check_normalized(gain)?;
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_normalized_rx_gain(h, gain, channel) }))
+    }
+
+    fn get_normalized_rx_gain(&self, channel: usize) -> SDRResult<f64> {
+        self.use_usrp(|h| {
+            let mut gain = 0.0;
+            unsafe { handle_uhd_err(uhd_usrp_get_normalized_rx_gain(h, channel, &mut gain)) }?;
+            Ok(gain)
+        })
+    }
+}
+
+impl FrontendCorrections for DeviceUHD {
+    fn set_rx_agc(&self, enable: bool, channel: usize) -> SDRResult<()> {
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_rx_agc(h, enable, channel) }))
+    }
+
+    fn set_rx_dc_offset_enabled(&self, enable: bool, channel: usize) -> SDRResult<()> {
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_rx_dc_offset_enabled(h, enable, channel) }))
+    }
+
+    fn set_rx_iq_balance_enabled(&self, enable: bool, channel: usize) -> SDRResult<()> {
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_rx_iq_balance_enabled(h, enable, channel) }))
+    }
+}
+
+impl LoControl for DeviceUHD {
+    fn tx_lo_names(&self, channel: usize) -> SDRResult<Vec<String>> {
+        self.get_strings(|h, out| unsafe { uhd_usrp_get_tx_lo_names(h, channel, out) })
+    }
+
+    fn set_tx_lo_source(&self, source: &LoSource, name: &str, channel: usize) -> SDRResult<()> {
+        let source = c_string("source", source.name())?;
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_tx_lo_source(h, source.as_ptr(), name.as_ptr(), channel) }))
+    }
+
+    fn get_tx_lo_source(&self, name: &str, channel: usize) -> SDRResult<LoSource> {
+        let name = c_string("name", name)?;
+        let source = self.use_usrp(|h| read_string(|buf, len| unsafe { uhd_usrp_get_tx_lo_source(h, name.as_ptr(), channel, buf, len) }))?;
+        Ok(source.as_str().into())
+    }
+
+    fn tx_lo_sources(&self, name: &str, channel: usize) -> SDRResult<Vec<LoSource>> {
+        let name = c_string("name", name)?;
+        let sources = self.get_strings(|h, out| unsafe { uhd_usrp_get_tx_lo_sources(h, name.as_ptr(), channel, out) })?;
+        Ok(sources.iter().map(|s| s.as_str().into()).collect())
+    }
+
+    fn set_tx_lo_export_enabled(&self, enabled: bool, name: &str, channel: usize) -> SDRResult<()> {
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_tx_lo_export_enabled(h, enabled, name.as_ptr(), channel) }))
+    }
+
+    fn get_tx_lo_export_enabled(&self, name: &str, channel: usize) -> SDRResult<bool> {
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| {
+            let mut enabled = false;
+            unsafe { handle_uhd_err(uhd_usrp_get_tx_lo_export_enabled(h, name.as_ptr(), channel, &mut enabled)) }?;
+            Ok(enabled)
+        })
+    }
+
+    fn set_tx_lo_freq(&self, freq: f64, name: &str, channel: usize) -> SDRResult<f64> {
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| {
+            let mut coerced = 0.0;
+            unsafe { handle_uhd_err(uhd_usrp_set_tx_lo_freq(h, freq, name.as_ptr(), channel, &mut coerced)) }?;
+            Ok(coerced)
+        })
+    }
+
+    fn get_tx_lo_freq(&self, name: &str, channel: usize) -> SDRResult<f64> {
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| {
+            let mut freq = 0.0;
+            unsafe { handle_uhd_err(uhd_usrp_get_tx_lo_freq(h, name.as_ptr(), channel, &mut freq)) }?;
+            Ok(freq)
+        })
+    }
+
+    fn rx_lo_names(&self, channel: usize) -> SDRResult<Vec<String>> {
+        self.get_strings(|h, out| unsafe { uhd_usrp_get_rx_lo_names(h, channel, out) })
+    }
+
+    fn set_rx_lo_source(&self, source: &LoSource, name: &str, channel: usize) -> SDRResult<()> {
+        let source = c_string("source", source.name())?;
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_rx_lo_source(h, source.as_ptr(), name.as_ptr(), channel) }))
+    }
+
+    fn get_rx_lo_source(&self, name: &str, channel: usize) -> SDRResult<LoSource> {
+        let name = c_string("name", name)?;
+        let source = self.use_usrp(|h| read_string(|buf, len| unsafe { uhd_usrp_get_rx_lo_source(h, name.as_ptr(), channel, buf, len) }))?;
+        Ok(source.as_str().into())
+    }
+
+    fn rx_lo_sources(&self, name: &str, channel: usize) -> SDRResult<Vec<LoSource>> {
+        let name = c_string("name", name)?;
+        let sources = self.get_strings(|h, out| unsafe { uhd_usrp_get_rx_lo_sources(h, name.as_ptr(), channel, out) })?;
+        Ok(sources.iter().map(|s| s.as_str().into()).collect())
+    }
+
+    fn set_rx_lo_export_enabled(&self, enabled: bool, name: &str, channel: usize) -> SDRResult<()> {
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_rx_lo_export_enabled(h, enabled, name.as_ptr(), channel) }))
+    }
+
+    fn get_rx_lo_export_enabled(&self, name: &str, channel: usize) -> SDRResult<bool> {
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| {
+            let mut enabled = false;
+            unsafe { handle_uhd_err(uhd_usrp_get_rx_lo_export_enabled(h, name.as_ptr(), channel, &mut enabled)) }?;
+            Ok(enabled)
+        })
+    }
+
+    fn set_rx_lo_freq(&self, freq: f64, name: &str, channel: usize) -> SDRResult<f64> {
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| {
+            let mut coerced = 0.0;
+            unsafe { handle_uhd_err(uhd_usrp_set_rx_lo_freq(h, freq, name.as_ptr(), channel, &mut coerced)) }?;
+            Ok(coerced)
+        })
+    }
+
+    fn get_rx_lo_freq(&self, name: &str, channel: usize) -> SDRResult<f64> {
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| {
+            let mut freq = 0.0;
+            unsafe { handle_uhd_err(uhd_usrp_get_rx_lo_freq(h, name.as_ptr(), channel, &mut freq)) }?;
+            Ok(freq)
+        })
+    }
+}
+
+impl Sensors for DeviceUHD {
+    fn sensor_names(&self, location: SensorLocation) -> SDRResult<Vec<String>> {
+        self.get_strings(|h, out| unsafe {
+            match location {
+                SensorLocation::Mboard(mboard) => uhd_usrp_get_mboard_sensor_names(h, mboard, out),
+                SensorLocation::Rx(channel) => uhd_usrp_get_rx_sensor_names(h, channel, out),
+                SensorLocation::Tx(channel) => uhd_usrp_get_tx_sensor_names(h, channel, out),
+            }
+        })
+    }
+
+    fn sensor(&self, name: &str, location: SensorLocation) -> SDRResult<Sensor> {
+        let name = c_string("name", name)?;
+        self.use_usrp(|h| {
+            let mut value = SensorValueHandle::new()?;
+            handle_uhd_err(unsafe {
+                match location {
+                    SensorLocation::Mboard(mboard) => uhd_usrp_get_mboard_sensor(h, name.as_ptr(), mboard, &mut value.0),
+                    SensorLocation::Rx(channel) => uhd_usrp_get_rx_sensor(h, name.as_ptr(), channel, &mut value.0),
+                    SensorLocation::Tx(channel) => uhd_usrp_get_tx_sensor(h, name.as_ptr(), channel, &mut value.0),
+                }
+            })?;
+            value.to_sensor()
+        })
+    }
+}
+
+impl CommandTime for DeviceUHD {
+    fn set_command_time(&self, time: TimeSpec, mboard: usize) -> SDRResult<()> {
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_command_time(h, time.full_secs, time.frac_secs, mboard) }))
+    }
+
+    fn clear_command_time(&self, mboard: usize) -> SDRResult<()> {
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_clear_command_time(h, mboard) }))
+    }
+}
+
+impl SubdevControl for DeviceUHD {
+    fn set_tx_subdev_spec(&self, spec: &SubdevSpec, mboard: usize) -> SDRResult<()> {
+        let spec = SubdevSpecHandle::new(&spec.to_string())?;
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_tx_subdev_spec(h, spec.0, mboard) }))
+    }
+
+    fn get_tx_subdev_spec(&self, mboard: usize) -> SDRResult<SubdevSpec> {
+        self.use_usrp(|h| {
+            let spec = SubdevSpecHandle::new("")?;
+            unsafe { handle_uhd_err(uhd_usrp_get_tx_subdev_spec(h, mboard, spec.0)) }?;
+            spec.to_spec()
+        })
+    }
+
+    fn tx_subdev_name(&self, channel: usize) -> SDRResult<String> {
+        self.use_usrp(|h| read_string(|buf, len| unsafe { uhd_usrp_get_tx_subdev_name(h, channel, buf, len) }))
+    }
+
+    fn set_rx_subdev_spec(&self, spec: &SubdevSpec, mboard: usize) -> SDRResult<()> {
+        let spec = SubdevSpecHandle::new(&spec.to_string())?;
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_rx_subdev_spec(h, spec.0, mboard) }))
+    }
+
+    fn get_rx_subdev_spec(&self, mboard: usize) -> SDRResult<SubdevSpec> {
+        self.use_usrp(|h| {
+            let spec = SubdevSpecHandle::new("")?;
+            unsafe { handle_uhd_err(uhd_usrp_get_rx_subdev_spec(h, mboard, spec.0)) }?;
+            spec.to_spec()
+        })
+    }
+
+    fn rx_subdev_name(&self, channel: usize) -> SDRResult<String> {
+        self.use_usrp(|h| read_string(|buf, len| unsafe { uhd_usrp_get_rx_subdev_name(h, channel, buf, len) }))
+    }
+}
+
+impl ClockControl for DeviceUHD {
+    fn set_clock_source(&self, source: &str, mboard: usize) -> SDRResult<()> {
+        let source = c_string("source", source)?;
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_clock_source(h, source.as_ptr(), mboard) }))
+    }
+
+    fn get_clock_source(&self, mboard: usize) -> SDRResult<String> {
+        self.use_usrp(|h| read_string(|buf, len| unsafe { uhd_usrp_get_clock_source(h, mboard, buf, len) }))
+    }
+
+    fn clock_sources(&self, mboard: usize) -> SDRResult<Vec<String>> {
+        self.get_strings(|h, out| unsafe { uhd_usrp_get_clock_sources(h, mboard, out) })
+    }
+
+    fn set_time_source(&self, source: &str, mboard: usize) -> SDRResult<()> {
+        let source = c_string("source", source)?;
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_time_source(h, source.as_ptr(), mboard) }))
+    }
+
+    fn get_time_source(&self, mboard: usize) -> SDRResult<String> {
+        self.use_usrp(|h| read_string(|buf, len| unsafe { uhd_usrp_get_time_source(h, mboard, buf, len) }))
+    }
+
+    fn time_sources(&self, mboard: usize) -> SDRResult<Vec<String>> {
+        self.get_strings(|h, out| unsafe { uhd_usrp_get_time_sources(h, mboard, out) })
+    }
+
+    fn set_clock_source_out(&self, enable: bool, mboard: usize) -> SDRResult<()> {
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_clock_source_out(h, enable, mboard) }))
+    }
+
+    fn set_time_source_out(&self, enable: bool, mboard: usize) -> SDRResult<()> {
+        self.use_usrp(|h| handle_uhd_err(unsafe { uhd_usrp_set_time_source_out(h, enable, mboard) }))
+    }
 }
 
 impl CreateTx<f32, TxUHD<f32>> for DeviceUHD {
@@ -363,6 +852,36 @@ impl Drop for DeviceUHD {
     }
 }
 
+pub(crate) fn c_string(key: &str, value: &str) -> SDRResult<CString> {
+    CString::new(value).map_err(|_| SDRError::Param {
+        key: key.into(),
+        value: value.into(),
+        msg: "contains a NUL byte".into(),
+    })
+}
+
+fn check_normalized(gain: f64) -> SDRResult<()> {
+    if !(0.0..=1.0).contains(&gain) {
+        return Err(SDRError::Param {
+            key: "gain".into(),
+            value: gain.to_string(),
+            msg: "normalized gain must be within 0..=1".into(),
+        });
+    }
+    Ok(())
+}
+
+/// Calls a UHD getter that writes a NUL terminated string into a buffer.
+pub(crate) fn read_string<F>(f: F) -> SDRResult<String>
+where
+    F: FnOnce(*mut std::os::raw::c_char, usize) -> uhd_error,
+{
+    let mut buffer = [0u8; 1024];
+    handle_uhd_err(f(buffer.as_mut_ptr() as _, buffer.len()))?;
+    let c = std::ffi::CStr::from_bytes_until_nul(&buffer).unwrap_or_default();
+    Ok(c.to_string_lossy().to_string())
+}
+
 fn new_uhd_tune_result_t() -> uhd_tune_result_t {
     uhd_tune_result_t {
         clipped_rf_freq: 0.0,
@@ -373,14 +892,31 @@ fn new_uhd_tune_result_t() -> uhd_tune_result_t {
     }
 }
 
-fn new_uhd_tune_request_t(freq: f64) -> uhd_tune_request_t {
-    let args = null_mut();
+fn new_uhd_tune_request_t(request: &TuneRequest, args: &CString) -> uhd_tune_request_t {
     uhd_tune_request_t {
-        target_freq: freq,
-        rf_freq_policy: uhd_tune_request_policy_t_UHD_TUNE_REQUEST_POLICY_AUTO,
-        rf_freq: 0.0,
-        dsp_freq_policy: uhd_tune_request_policy_t_UHD_TUNE_REQUEST_POLICY_AUTO,
-        dsp_freq: 0.0,
-        args,
+        target_freq: request.target_freq,
+        rf_freq_policy: tune_policy(request.rf_freq_policy),
+        rf_freq: request.rf_freq,
+        dsp_freq_policy: tune_policy(request.dsp_freq_policy),
+        dsp_freq: request.dsp_freq,
+        args: args.as_ptr() as _,
+    }
+}
+
+fn tune_policy(policy: TunePolicy) -> uhd_tune_request_policy_t {
+    match policy {
+        TunePolicy::None => uhd_tune_request_policy_t_UHD_TUNE_REQUEST_POLICY_NONE,
+        TunePolicy::Auto => uhd_tune_request_policy_t_UHD_TUNE_REQUEST_POLICY_AUTO,
+        TunePolicy::Manual => uhd_tune_request_policy_t_UHD_TUNE_REQUEST_POLICY_MANUAL,
+    }
+}
+
+fn tune_result(r: &uhd_tune_result_t) -> TuneResult {
+    TuneResult {
+        clipped_rf_freq: r.clipped_rf_freq,
+        target_rf_freq: r.target_rf_freq,
+        actual_rf_freq: r.actual_rf_freq,
+        target_dsp_freq: r.target_dsp_freq,
+        actual_dsp_freq: r.actual_dsp_freq,
     }
 }
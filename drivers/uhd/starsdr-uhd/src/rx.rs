@@ -1,6 +1,6 @@
 use std::ffi::c_void;
 use std::marker::PhantomData;
-use starsdr_interface::{Complex, Rx, SDRError, SDRResult};
+use starsdr_interface::{check_channel_buffers, Complex, RecvInfo, Rx, RxMetadata, SDRError, SDRResult, Sample, TimeSpec};
 use uhd_sys::*;
 use crate::errors::handle_uhd_err;
 use crate::structs::{RxMetadataHandle, RxStreamerHandle};
@@ -9,40 +9,73 @@ pub struct RxUHD<T: Send> {
     pub(crate) streamer: RxStreamerHandle,
     pub sample_num_max: usize,
     pub(crate) md: RxMetadataHandle,
+    pub(crate) channels: usize,
     pub(crate) _t: PhantomData<T>,
 }
 
+impl<T: Send> RxUHD<T> {
+    fn metadata(&self) -> SDRResult<RxMetadata> {
+        let h = self.md.0;
+        let mut md = RxMetadata::default();
+        unsafe {
+            let mut has_time_spec = false;
+            handle_uhd_err(uhd_rx_metadata_has_time_spec(h, &mut has_time_spec))?;
+            if has_time_spec {
+                let mut t = TimeSpec::default();
+                handle_uhd_err(uhd_rx_metadata_time_spec(h, &mut t.full_secs, &mut t.frac_secs))?;
+                md.time_spec = Some(t);
+            }
+            handle_uhd_err(uhd_rx_metadata_start_of_burst(h, &mut md.start_of_burst))?;
+            handle_uhd_err(uhd_rx_metadata_end_of_burst(h, &mut md.end_of_burst))?;
+            handle_uhd_err(uhd_rx_metadata_more_fragments(h, &mut md.more_fragments))?;
+            handle_uhd_err(uhd_rx_metadata_fragment_offset(h, &mut md.fragment_offset))?;
+            handle_uhd_err(uhd_rx_metadata_out_of_sequence(h, &mut md.out_of_sequence))?;
+        }
+        Ok(md)
+    }
+}
 
-impl <T:Send> Rx<T> for RxUHD<T> {
+impl <T: Sample> Rx<T> for RxUHD<T> {
+    fn channel_count(&self) -> usize {
+        self.channels
+    }
 
-    fn recv(&mut self) -> SDRResult<Vec<Complex<T>>> {
+    fn sample_num_max(&self) -> usize {
+        self.sample_num_max
+    }
 
+    fn recv_multi_into(&mut self, bufs: &mut [&mut [Complex<T>]]) -> SDRResult<RecvInfo> {
+        check_channel_buffers(self.channels, bufs.len())?;
+        let num = bufs.iter().map(|b| b.len()).min().unwrap_or(0);
+        let mut ptrs: Vec<_> = bufs.iter_mut().map(|b| b.as_mut_ptr() as *mut c_void).collect();
 
         unsafe {
-            let num = self.sample_num_max;
-            let mut buf: Vec<T> = Vec::with_capacity(num*2);
-            let ptr = buf.as_mut_ptr();
-            std::mem::forget(buf);
-
             let mut n = 0;
             handle_uhd_err(uhd_rx_streamer_recv(
                 self.streamer.0,
-                &mut (ptr as *mut c_void),
+                ptrs.as_mut_ptr(),
                 num,
                 &mut self.md.0,
                 1.0,
                 false,
                 &mut n
             ) )?;
-            let p = ptr as *mut Complex<T>;
-            let buf = Vec::from_raw_parts(p, n, num);
             let mut code = 0;
             uhd_rx_metadata_error_code(self.md.0, &mut code);
 
             #[allow(non_upper_case_globals)]
             match code {
-                uhd_rx_metadata_error_code_t_UHD_RX_METADATA_ERROR_CODE_NONE=>Ok(buf),
-                uhd_rx_metadata_error_code_t_UHD_RX_METADATA_ERROR_CODE_OVERFLOW=>Err(SDRError::Overflow),
+                uhd_rx_metadata_error_code_t_UHD_RX_METADATA_ERROR_CODE_NONE=>Ok(RecvInfo { samples: n, metadata: self.metadata()? }),
+                // UHD also reports dropped packets as overflows, told apart by
+                // the out of sequence flag. Those still deliver samples.
+                uhd_rx_metadata_error_code_t_UHD_RX_METADATA_ERROR_CODE_OVERFLOW=>{
+                    let metadata = self.metadata()?;
+                    if metadata.out_of_sequence {
+                        Ok(RecvInfo { samples: n, metadata })
+                    } else {
+                        Err(SDRError::Overflow)
+                    }
+                }
                 uhd_rx_metadata_error_code_t_UHD_RX_METADATA_ERROR_CODE_TIMEOUT=>Err(SDRError::TimeOut),
                 _=> Err(SDRError::Unknown(format!("recv fail: uhd meta[{code}]")))
             }
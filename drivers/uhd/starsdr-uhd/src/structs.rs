@@ -1,10 +1,11 @@
 use std::ffi::{CStr};
 use std::ptr::null_mut;
 
-use starsdr_interface::SDRResult;
+use starsdr_interface::{MetaRange, Range, SDRError, SDRResult, Sensor, SensorValue, SubdevSpec, TxMetadata};
 use uhd_sys::*;
 
 use crate::errors::handle_uhd_err;
+use crate::read_string;
 
 pub(crate) struct UHDStringVector {
     size: Option<usize>,
@@ -93,6 +94,92 @@ impl Drop for $name{
 type_uhd_handle!(TxStreamerHandle, uhd_tx_streamer_handle, uhd_tx_streamer_make, uhd_tx_streamer_free);
 type_uhd_handle!(RxStreamerHandle, uhd_rx_streamer_handle, uhd_rx_streamer_make, uhd_rx_streamer_free);
 type_uhd_handle!(RxMetadataHandle, uhd_rx_metadata_handle, uhd_rx_metadata_make, uhd_rx_metadata_free);
+type_uhd_handle!(AsyncMetadataHandle, uhd_async_metadata_handle, uhd_async_metadata_make, uhd_async_metadata_free);
+type_uhd_handle!(MetaRangeHandle, uhd_meta_range_handle, uhd_meta_range_make, uhd_meta_range_free);
+
+impl MetaRangeHandle {
+    pub fn to_meta_range(&self) -> SDRResult<MetaRange> {
+        unsafe {
+            let mut size = 0;
+            handle_uhd_err(uhd_meta_range_size(self.0, &mut size))?;
+            let mut ranges = Vec::with_capacity(size);
+            for i in 0..size {
+                let mut r = uhd_range_t { start: 0.0, stop: 0.0, step: 0.0 };
+                handle_uhd_err(uhd_meta_range_at(self.0, i, &mut r))?;
+                ranges.push(Range::new(r.start, r.stop, r.step));
+            }
+            Ok(MetaRange::new(ranges))
+        }
+    }
+}
+type_uhd_handle!(MboardEepromHandle, uhd_mboard_eeprom_handle, uhd_mboard_eeprom_make, uhd_mboard_eeprom_free);
+type_uhd_handle!(DboardEepromHandle, uhd_dboard_eeprom_handle, uhd_dboard_eeprom_make, uhd_dboard_eeprom_free);
+type_uhd_handle!(SensorValueHandle, uhd_sensor_value_handle, uhd_sensor_value_make, uhd_sensor_value_free);
+
+impl SensorValueHandle {
+    #[allow(non_upper_case_globals)]
+    pub fn to_sensor(&self) -> SDRResult<Sensor> {
+        let h = self.0;
+        unsafe {
+            let name = read_string(|buf, len| uhd_sensor_value_name(h, buf, len))?;
+            let unit = read_string(|buf, len| uhd_sensor_value_unit(h, buf, len))?;
+            let mut data_type = 0;
+            handle_uhd_err(uhd_sensor_value_data_type(h, &mut data_type))?;
+            let value = match data_type {
+                uhd_sensor_value_data_type_t_UHD_SENSOR_VALUE_BOOLEAN => {
+                    let mut v = false;
+                    handle_uhd_err(uhd_sensor_value_to_bool(h, &mut v))?;
+                    SensorValue::Bool(v)
+                }
+                uhd_sensor_value_data_type_t_UHD_SENSOR_VALUE_INTEGER => {
+                    let mut v = 0;
+                    handle_uhd_err(uhd_sensor_value_to_int(h, &mut v))?;
+                    SensorValue::Int(v as i64)
+                }
+                uhd_sensor_value_data_type_t_UHD_SENSOR_VALUE_REALNUM => {
+                    let mut v = 0.0;
+                    handle_uhd_err(uhd_sensor_value_to_realnum(h, &mut v))?;
+                    SensorValue::Real(v)
+                }
+                uhd_sensor_value_data_type_t_UHD_SENSOR_VALUE_STRING => {
+                    SensorValue::String(read_string(|buf, len| uhd_sensor_value_value(h, buf, len))?)
+                }
+                other => return Err(SDRError::Unknown(format!("sensor {} has data type {}", name, other))),
+            };
+            Ok(Sensor { name, unit, value })
+        }
+    }
+}
+pub(crate) struct SubdevSpecHandle(pub uhd_subdev_spec_handle);
+
+unsafe impl Send for SubdevSpecHandle {}
+
+impl SubdevSpecHandle {
+    pub fn new(markup: &str) -> SDRResult<Self> {
+        let markup = crate::c_string("subdev", markup)?;
+        unsafe {
+            let mut handle = null_mut();
+            handle_uhd_err(uhd_subdev_spec_make(&mut handle, markup.as_ptr()))?;
+            Ok(Self(handle))
+        }
+    }
+
+    pub fn to_spec(&self) -> SDRResult<SubdevSpec> {
+        let markup = read_string(|buf, len| unsafe { uhd_subdev_spec_to_string(self.0, buf, len) })?;
+        markup.parse()
+    }
+}
+
+impl Drop for SubdevSpecHandle {
+    fn drop(&mut self) {
+        unsafe {
+            uhd_subdev_spec_free(&mut self.0);
+        }
+    }
+}
+
+// UHD allows `recv_async_msg` to run on another thread while sending.
+unsafe impl Sync for TxStreamerHandle {}
 
 
 pub(crate) struct TxMetadataHandle(pub uhd_tx_metadata_handle);
@@ -100,15 +187,16 @@ pub(crate) struct TxMetadataHandle(pub uhd_tx_metadata_handle);
 unsafe impl Send for TxMetadataHandle {}
 
 impl TxMetadataHandle {
-    pub fn new() -> SDRResult<Self> {
+    pub fn new(md: &TxMetadata) -> SDRResult<Self> {
         unsafe {
-            let mut md = null_mut();
+            let time_spec = md.time_spec.unwrap_or_default();
+            let mut handle = null_mut();
             handle_uhd_err(uhd_tx_metadata_make(
-                &mut md,
-                false, 0,
-                0.1, true, false))?;
+                &mut handle,
+                md.time_spec.is_some(), time_spec.full_secs,
+                time_spec.frac_secs, md.start_of_burst, md.end_of_burst))?;
 
-            Ok(Self(md))
+            Ok(Self(handle))
         }
     }
 }
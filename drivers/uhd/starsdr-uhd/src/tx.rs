@@ -1,19 +1,52 @@
+use std::cell::RefCell;
 use std::marker::PhantomData;
+use std::sync::Arc;
 use starsdr_interface::*;
-use crate::structs::{TxMetadataHandle, TxStreamerHandle};
+use crate::structs::{AsyncMetadataHandle, TxMetadataHandle, TxStreamerHandle};
 use std::ffi::c_void;
-use uhd_sys::uhd_tx_streamer_send;
+use uhd_sys::*;
 use crate::errors::handle_uhd_err;
 
 pub struct TxUHD<T: Send> {
-    pub(crate) streamer: TxStreamerHandle,
+    pub(crate) streamer: Arc<TxStreamerHandle>,
     pub sample_num_max: usize,
+    pub(crate) channels: usize,
+    /// Metadata handle of the last send, rebuilt only when the metadata changes.
+    pub(crate) md: RefCell<Option<(TxMetadata, TxMetadataHandle)>>,
     pub(crate) _t: PhantomData<T>,
 }
 
+impl<T: Send> TxUHD<T> {
+    /// Async messages (burst acks, underflows, late packets) reported by the
+    /// device for this stream. The returned iterator can be moved to another
+    /// thread and polled while this one keeps sending.
+    ///
+    /// `send` returns as soon as UHD has queued the samples, so it never
+    /// reports an underflow; watch these events for `SDRError::Underflow`.
+    pub fn events(&self, timeout: f64) -> SDRResult<TxEvents> {
+        Ok(TxEvents {
+            streamer: self.streamer.clone(),
+            md: AsyncMetadataHandle::new()?,
+            timeout,
+        })
+    }
+}
+
 impl<T: Send> Tx<T> for TxUHD<T> {
-    fn send(&self, v: &[Complex<T>]) -> SDRResult<usize> {
-        let data_len = v.len();
+    fn channel_count(&self) -> usize {
+        self.channels
+    }
+
+    fn send_multi_with(&self, bufs: &[&[Complex<T>]], md: &TxMetadata) -> SDRResult<usize> {
+        check_channel_buffers(self.channels, bufs.len())?;
+        let data_len = bufs.first().map_or(0, |b| b.len());
+        if bufs.iter().any(|b| b.len() != data_len) {
+            return Err(SDRError::Param {
+                key: "bufs".into(),
+                value: format!("{:?}", bufs.iter().map(|b| b.len()).collect::<Vec<_>>()),
+                msg: "channel buffers differ in length".into(),
+            });
+        }
         if data_len > self.sample_num_max {
             return Err(SDRError::Param {
                 key: "v".into(),
@@ -22,16 +55,88 @@ impl<T: Send> Tx<T> for TxUHD<T> {
             });
         }
         let mut items_sent = 0;
-        let mut md = TxMetadataHandle::new()?;
+        let mut cached = self.md.borrow_mut();
+        if cached.as_ref().map(|(last, _)| last != md).unwrap_or(true) {
+            *cached = Some((*md, TxMetadataHandle::new(md)?));
+        }
+        let (_, handle) = cached.as_mut().unwrap();
+        let mut ptrs: Vec<_> = bufs.iter().map(|b| b.as_ptr() as *const c_void).collect();
         unsafe {
-            let buf = v.as_ptr() as *const c_void;
-            let buf = &*buf;
-
             handle_uhd_err(uhd_tx_streamer_send(
-                self.streamer.0, &mut (buf as *const c_void),
-                v.len(), &mut md.0, 0.1, &mut items_sent))?;
+                self.streamer.0, ptrs.as_mut_ptr(),
+                data_len, &mut handle.0, 0.1, &mut items_sent))?;
         }
 
         Ok(items_sent)
     }
 }
+
+pub struct TxEvents {
+    streamer: Arc<TxStreamerHandle>,
+    md: AsyncMetadataHandle,
+    /// Seconds to wait for each message.
+    pub timeout: f64,
+}
+
+impl TxEvents {
+    /// Waits up to `timeout` seconds for the next message, `None` if none came.
+    pub fn recv(&mut self) -> SDRResult<Option<TxEvent>> {
+        let mut valid = false;
+        unsafe {
+            handle_uhd_err(uhd_tx_streamer_recv_async_msg(
+                self.streamer.0, &mut self.md.0, self.timeout, &mut valid))?;
+        }
+        if !valid {
+            return Ok(None);
+        }
+        self.event().map(Some)
+    }
+
+    #[allow(non_upper_case_globals)]
+    fn event(&self) -> SDRResult<TxEvent> {
+        let h = self.md.0;
+        let mut channel = 0;
+        let mut has_time_spec = false;
+        let mut full_secs = 0;
+        let mut frac_secs = 0.0;
+        let mut code = 0;
+        unsafe {
+            handle_uhd_err(uhd_async_metadata_channel(h, &mut channel))?;
+            handle_uhd_err(uhd_async_metadata_has_time_spec(h, &mut has_time_spec))?;
+            if has_time_spec {
+                handle_uhd_err(uhd_async_metadata_time_spec(h, &mut full_secs, &mut frac_secs))?;
+            }
+            handle_uhd_err(uhd_async_metadata_event_code(h, &mut code))?;
+        }
+        let kind = match code {
+            uhd_async_metadata_event_code_t_UHD_ASYNC_METADATA_EVENT_CODE_BURST_ACK => TxEventKind::BurstAck,
+            uhd_async_metadata_event_code_t_UHD_ASYNC_METADATA_EVENT_CODE_UNDERFLOW => TxEventKind::Underflow,
+            uhd_async_metadata_event_code_t_UHD_ASYNC_METADATA_EVENT_CODE_SEQ_ERROR => TxEventKind::SequenceError,
+            uhd_async_metadata_event_code_t_UHD_ASYNC_METADATA_EVENT_CODE_TIME_ERROR => TxEventKind::TimeError,
+            uhd_async_metadata_event_code_t_UHD_ASYNC_METADATA_EVENT_CODE_UNDERFLOW_IN_PACKET => TxEventKind::UnderflowInPacket,
+            uhd_async_metadata_event_code_t_UHD_ASYNC_METADATA_EVENT_CODE_SEQ_ERROR_IN_BURST => TxEventKind::SequenceErrorInBurst,
+            uhd_async_metadata_event_code_t_UHD_ASYNC_METADATA_EVENT_CODE_USER_PAYLOAD => {
+                let mut payload = [0u32; 4];
+                unsafe {
+                    handle_uhd_err(uhd_async_metadata_user_payload(h, payload.as_mut_ptr()))?;
+                }
+                TxEventKind::UserPayload(payload)
+            }
+            other => TxEventKind::Unknown(other as u32),
+        };
+        Ok(TxEvent {
+            channel,
+            time_spec: has_time_spec.then(|| TimeSpec::new(full_secs, frac_secs)),
+            kind,
+        })
+    }
+}
+
+/// Yields events until one `recv` times out.
+impl Iterator for TxEvents {
+    type Item = SDRResult<TxEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv().transpose()
+    }
+}
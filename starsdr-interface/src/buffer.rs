@@ -0,0 +1,162 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex},
+};
+
+use num::Complex;
+
+use crate::{Rx, SDRResult, Sample};
+
+type Free<T> = Arc<Mutex<Vec<Vec<Complex<T>>>>>;
+
+/// Fixed size sample buffers that go back to the pool when dropped, so a
+/// steady stream of `recv_into` calls reuses memory instead of allocating.
+pub struct BufferPool<T: Sample> {
+    free: Free<T>,
+    size: usize,
+}
+
+impl<T: Sample> Clone for BufferPool<T> {
+    fn clone(&self) -> Self {
+        Self {
+            free: self.free.clone(),
+            size: self.size,
+        }
+    }
+}
+
+impl<T: Sample> BufferPool<T> {
+    /// Preallocates `count` buffers of `size` samples each.
+    pub fn new(size: usize, count: usize) -> Self {
+        let mut free = Vec::with_capacity(count);
+        free.resize_with(count, || vec![Complex::default(); size]);
+        Self {
+            free: Arc::new(Mutex::new(free)),
+            size,
+        }
+    }
+
+    pub fn buffer_size(&self) -> usize {
+        self.size
+    }
+
+    pub fn available(&self) -> usize {
+        self.free.lock().unwrap().len()
+    }
+
+    /// Takes a free buffer, allocating a new one only if all are in use.
+    pub fn get(&self) -> PooledBuffer<T> {
+        let buf = self
+            .free
+            .lock()
+            .unwrap()
+            .pop()
+            .unwrap_or_else(|| vec![Complex::default(); self.size]);
+        PooledBuffer {
+            len: buf.len(),
+            buf,
+            free: self.free.clone(),
+        }
+    }
+
+    pub fn recv<R: Rx<T> + ?Sized>(&self, rx: &mut R) -> SDRResult<PooledBuffer<T>> {
+        let mut buf = self.get();
+        let info = rx.recv_into(buf.as_full_mut())?;
+        buf.set_len(info.samples);
+        Ok(buf)
+    }
+}
+
+/// Derefs to the first `len()` samples of the underlying buffer.
+pub struct PooledBuffer<T: Sample> {
+    buf: Vec<Complex<T>>,
+    len: usize,
+    free: Free<T>,
+}
+
+impl<T: Sample> PooledBuffer<T> {
+    /// The whole buffer regardless of the current length, for receiving into.
+    pub fn as_full_mut(&mut self) -> &mut [Complex<T>] {
+        &mut self.buf
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn set_len(&mut self, len: usize) {
+        self.len = len.min(self.buf.len());
+    }
+}
+
+impl<T: Sample> Deref for PooledBuffer<T> {
+    type Target = [Complex<T>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.buf[..self.len]
+    }
+}
+
+impl<T: Sample> DerefMut for PooledBuffer<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buf[..self.len]
+    }
+}
+
+impl<T: Sample> Drop for PooledBuffer<T> {
+    fn drop(&mut self) {
+        let buf = std::mem::take(&mut self.buf);
+        self.free.lock().unwrap().push(buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{check_channel_buffers, RecvInfo};
+
+    struct Counter(i16);
+
+    impl Rx<i16> for Counter {
+        fn channel_count(&self) -> usize {
+            1
+        }
+
+        fn sample_num_max(&self) -> usize {
+            4
+        }
+
+        fn recv_multi_into(&mut self, bufs: &mut [&mut [Complex<i16>]]) -> SDRResult<RecvInfo> {
+            check_channel_buffers(1, bufs.len())?;
+            bufs[0][0] = Complex::new(self.0, 0);
+            self.0 += 1;
+            Ok(RecvInfo {
+                samples: 1,
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn test_reuse() {
+        let pool = BufferPool::new(16, 1);
+        let mut rx = Counter(0);
+
+        let a = pool.recv(&mut rx).unwrap();
+        let ptr = a.as_ptr();
+        assert_eq!(&[Complex::new(0, 0)], &a[..]);
+        assert_eq!(0, pool.available());
+        drop(a);
+        assert_eq!(1, pool.available());
+
+        let c = pool.recv(&mut rx).unwrap();
+        assert_eq!(ptr, c.as_ptr());
+        assert_eq!(&[Complex::new(1, 0)], &c[..]);
+    }
+
+    #[test]
+    fn test_default_recv() {
+        let mut rx = Counter(7);
+        assert_eq!(vec![Complex::new(7, 0)], rx.recv().unwrap());
+    }
+}
@@ -0,0 +1,284 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use crate::{SDRError, SDRResult};
+
+/// What a driver knows about a device before opening it, as `key=value`
+/// fields in the style of UHD device addresses
+/// (`type=b200,serial=3150360,product=B210`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// `SDRDriver::NAME` of the driver that found the device.
+    pub driver: String,
+    pub values: BTreeMap<String, String>,
+}
+
+impl DeviceInfo {
+    pub fn new(driver: &str) -> Self {
+        Self {
+            driver: driver.into(),
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Parses comma separated `key=value` pairs. Pairs without `=` and empty
+    /// values are skipped.
+    pub fn parse(driver: &str, args: &str) -> Self {
+        let mut info = Self::new(driver);
+        for (k, v) in args.split(',').filter_map(|p| p.split_once('=')) {
+            let (k, v) = (k.trim(), v.trim());
+            if !k.is_empty() && !v.is_empty() {
+                info.values.insert(k.into(), v.into());
+            }
+        }
+        info
+    }
+
+    pub fn with(mut self, key: &str, value: impl Display) -> Self {
+        self.values.insert(key.into(), value.to_string());
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|v| v.as_str())
+    }
+
+    pub fn device_type(&self) -> Option<&str> {
+        self.get("type")
+    }
+
+    pub fn serial(&self) -> Option<&str> {
+        self.get("serial")
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.get("name")
+    }
+
+    pub fn product(&self) -> Option<&str> {
+        self.get("product")
+    }
+
+    pub fn addr(&self) -> Option<&str> {
+        self.get("addr")
+    }
+}
+
+impl Display for DeviceInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "driver={}", self.driver)?;
+        for (k, v) in &self.values {
+            write!(f, ",{}={}", k, v)?;
+        }
+        Ok(())
+    }
+}
+
+/// Selects devices whose fields equal all of the filter's fields. The key
+/// `driver` matches `DeviceInfo::driver`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeviceFilter {
+    pub values: BTreeMap<String, String>,
+}
+
+impl DeviceFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, key: &str, value: &str) -> Self {
+        self.values.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn driver(self, driver: &str) -> Self {
+        self.with("driver", driver)
+    }
+
+    pub fn serial(self, serial: &str) -> Self {
+        self.with("serial", serial)
+    }
+
+    pub fn product(self, product: &str) -> Self {
+        self.with("product", product)
+    }
+
+    pub fn device_type(self, device_type: &str) -> Self {
+        self.with("type", device_type)
+    }
+
+    pub fn matches(&self, info: &DeviceInfo) -> bool {
+        self.values.iter().all(|(k, v)| match k.as_str() {
+            "driver" => info.driver == *v,
+            _ => info.get(k) == Some(v),
+        })
+    }
+}
+
+/// Keys that identify a device, as opposed to options for opening it.
+const ID_KEYS: &[&str] = &["driver", "serial", "type", "product", "addr", "name"];
+
+/// Arguments for opening a device directly, e.g.
+/// `driver=uhd,addr=192.168.10.2,master_clock_rate=200e6`. The `driver` key
+/// picks the driver, the rest is passed on to it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeviceArgs {
+    pub values: BTreeMap<String, String>,
+}
+
+impl DeviceArgs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, key: &str, value: impl Display) -> Self {
+        self.values.insert(key.into(), value.to_string());
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|v| v.as_str())
+    }
+
+    /// Parses the value of `key`, `None` if it is not set.
+    pub fn get_as<T: FromStr>(&self, key: &str) -> SDRResult<Option<T>> {
+        self.get(key)
+            .map(|v| {
+                v.parse().map_err(|_| SDRError::Param {
+                    key: key.into(),
+                    value: v.into(),
+                    msg: format!("expect {}", std::any::type_name::<T>()),
+                })
+            })
+            .transpose()
+    }
+
+    pub fn driver(&self) -> Option<&str> {
+        self.get("driver")
+    }
+
+    /// Everything except `driver`, in `key=value,...` form.
+    pub fn driver_args(&self) -> String {
+        self.values
+            .iter()
+            .filter(|(k, _)| *k != "driver")
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// A filter on the identifying arguments (driver, serial, type, product,
+    /// addr and name). Open options such as `master_clock_rate` are left out.
+    pub fn filter(&self) -> DeviceFilter {
+        DeviceFilter {
+            values: self
+                .values
+                .iter()
+                .filter(|(k, _)| ID_KEYS.contains(&k.as_str()))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl FromStr for DeviceArgs {
+    type Err = SDRError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut args = Self::new();
+        for pair in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let err = |msg: &str| SDRError::Param {
+                key: "args".into(),
+                value: pair.into(),
+                msg: msg.into(),
+            };
+            let (k, v) = pair
+                .split_once('=')
+                .ok_or_else(|| err("expect key=value"))?;
+            let k = k.trim();
+            if k.is_empty() {
+                return Err(err("empty key"));
+            }
+            if args.values.insert(k.into(), v.trim().into()).is_some() {
+                return Err(err("duplicate key"));
+            }
+        }
+        Ok(args)
+    }
+}
+
+impl Display for DeviceArgs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (k, v)) in self.values.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}={}", k, v)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let info = DeviceInfo::parse("uhd", "type=b200,name=,serial=3150360,product=B210");
+        assert_eq!(Some("b200"), info.device_type());
+        assert_eq!(Some("3150360"), info.serial());
+        assert_eq!(Some("B210"), info.product());
+        assert_eq!(None, info.name());
+        assert_eq!(
+            "driver=uhd,product=B210,serial=3150360,type=b200",
+            info.to_string()
+        );
+    }
+
+    #[test]
+    fn test_filter() {
+        let info = DeviceInfo::new("uhd")
+            .with("serial", "31")
+            .with("type", "b200");
+        assert!(DeviceFilter::new().matches(&info));
+        assert!(DeviceFilter::new()
+            .serial("31")
+            .driver("uhd")
+            .matches(&info));
+        assert!(!DeviceFilter::new().serial("32").matches(&info));
+        assert!(!DeviceFilter::new().driver("mock").matches(&info));
+        assert!(!DeviceFilter::new().product("B210").matches(&info));
+    }
+
+    #[test]
+    fn test_args() {
+        let args: DeviceArgs = "driver=uhd, addr=192.168.10.2,master_clock_rate=200e6,"
+            .parse()
+            .unwrap();
+        assert_eq!(Some("uhd"), args.driver());
+        assert_eq!(
+            Some(200e6),
+            args.get_as::<f64>("master_clock_rate").unwrap()
+        );
+        assert_eq!(None, args.get_as::<f64>("rate").unwrap());
+        assert!(matches!(
+            args.get_as::<u32>("addr"),
+            Err(SDRError::Param { .. })
+        ));
+        assert_eq!(
+            "addr=192.168.10.2,master_clock_rate=200e6",
+            args.driver_args()
+        );
+        assert_eq!(args, args.to_string().parse().unwrap());
+        assert_eq!(
+            DeviceFilter::new()
+                .driver("uhd")
+                .with("addr", "192.168.10.2"),
+            args.filter()
+        );
+
+        assert!("addr".parse::<DeviceArgs>().is_err());
+        assert!("=1".parse::<DeviceArgs>().is_err());
+        assert!("a=1,a=2".parse::<DeviceArgs>().is_err());
+    }
+}
@@ -20,6 +20,18 @@ pub enum SDRError {
     TimeOut,
     #[error("Overflow")]
     Overflow,
+    /// TX ran out of samples. Sends do not wait for the device, so this only
+    /// comes from TX events, see `TxEvent::error`.
+    #[error("Underflow")]
+    Underflow,
+    #[error("Sequence error on channel {0}")]
+    SequenceError(usize),
+    #[error("Late command on channel {0}")]
+    LateCommand(usize),
+    #[error("End of stream")]
+    EndOfStream,
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 pub type SDRResult<T> = Result<T, SDRError>;
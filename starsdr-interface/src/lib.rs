@@ -1,17 +1,69 @@
+mod buffer;
+mod device;
 mod error;
+mod metadata;
+mod range;
+mod sample;
+mod sensor;
+mod subdev;
+mod time;
+mod tune;
 
+use async_trait::async_trait;
+pub use buffer::{BufferPool, PooledBuffer};
+pub use device::{DeviceArgs, DeviceFilter, DeviceInfo};
 pub use error::{SDRError, SDRResult};
-use std::{fmt::Display};
+pub use metadata::{RecvInfo, RxMetadata, TxEvent, TxEventKind, TxMetadata};
+pub use range::{MetaRange, Range};
+pub use sample::{Sample, SampleFormat, SAMPLE_NUM_MAX};
+pub use sensor::{Sensor, SensorLocation, SensorValue};
+pub use subdev::{SubdevPair, SubdevSpec};
+pub use time::TimeSpec;
+pub use tune::{LoSource, TunePolicy, TuneRequest, TuneResult, ALL_LOS};
+use std::{fmt::Display, thread, time::{Duration, Instant}};
 pub use num::{Complex};
 
 
 pub trait SDRDriver: Send {
+    /// Short name, also used as `DeviceInfo::driver`.
+    const NAME: &'static str;
     type Item: SDRDevice;
     fn list(&self) -> SDRResult<Vec<Self::Item>>;
+
+    fn list_with(&self, filter: &DeviceFilter) -> SDRResult<Vec<Self::Item>> {
+        let mut devices = self.list()?;
+        devices.retain(|d| filter.matches(&d.info()));
+        Ok(devices)
+    }
+
+    /// Opens the device `args` point to. By default this is the first listed
+    /// device matching `args.filter()`, and other arguments are ignored;
+    /// drivers that take open options or can address a device directly
+    /// override this.
+    fn open(&self, args: &DeviceArgs) -> SDRResult<Self::Item> {
+        let mut device = self
+            .list_with(&args.filter())?
+            .into_iter()
+            .next()
+            .ok_or(SDRError::NotFound)?;
+        device.open()?;
+        Ok(device)
+    }
 }
 
 pub trait SDRDevice: Send + Display {
+    fn info(&self) -> DeviceInfo;
     fn open(&mut self) -> SDRResult<()>;
+
+    fn driver_name(&self) -> String {
+        self.info().driver
+    }
+    fn serial(&self) -> Option<String> {
+        self.info().serial().map(String::from)
+    }
+    fn product(&self) -> Option<String> {
+        self.info().product().map(String::from)
+    }
     fn tx_channel_count(&self) -> SDRResult<usize>;
     fn rx_channel_count(&self) -> SDRResult<usize>;
     fn set_tx_rate(&self, rate: f64, channel: usize) -> SDRResult<()>;
@@ -30,6 +82,193 @@ pub trait SDRDevice: Send + Display {
     fn get_rx_gain(&self, channel: usize) -> SDRResult<f64>;
     fn set_rx_bandwidth(&self, bw: f64, channel: usize) -> SDRResult<()>;
     fn get_rx_bandwidth(&self, channel: usize) -> SDRResult<f64>;
+
+    /// Tune with explicit control over the LO and DSP stages.
+    fn tune_tx(&self, _request: &TuneRequest, _channel: usize) -> SDRResult<TuneResult> {
+        Err(SDRError::NotSupport("tune_tx".into()))
+    }
+    fn tune_rx(&self, _request: &TuneRequest, _channel: usize) -> SDRResult<TuneResult> {
+        Err(SDRError::NotSupport("tune_rx".into()))
+    }
+
+    fn tx_rates(&self, _channel: usize) -> SDRResult<MetaRange> {
+        Err(SDRError::NotSupport("tx_rates".into()))
+    }
+    fn tx_freq_range(&self, _channel: usize) -> SDRResult<MetaRange> {
+        Err(SDRError::NotSupport("tx_freq_range".into()))
+    }
+    fn tx_gain_range(&self, _channel: usize) -> SDRResult<MetaRange> {
+        Err(SDRError::NotSupport("tx_gain_range".into()))
+    }
+    fn tx_bandwidth_range(&self, _channel: usize) -> SDRResult<MetaRange> {
+        Err(SDRError::NotSupport("tx_bandwidth_range".into()))
+    }
+    fn rx_rates(&self, _channel: usize) -> SDRResult<MetaRange> {
+        Err(SDRError::NotSupport("rx_rates".into()))
+    }
+    fn rx_freq_range(&self, _channel: usize) -> SDRResult<MetaRange> {
+        Err(SDRError::NotSupport("rx_freq_range".into()))
+    }
+    fn rx_gain_range(&self, _channel: usize) -> SDRResult<MetaRange> {
+        Err(SDRError::NotSupport("rx_gain_range".into()))
+    }
+    fn rx_bandwidth_range(&self, _channel: usize) -> SDRResult<MetaRange> {
+        Err(SDRError::NotSupport("rx_bandwidth_range".into()))
+    }
+
+    fn set_tx_antenna(&self, _antenna: &str, _channel: usize) -> SDRResult<()> {
+        Err(SDRError::NotSupport("set_tx_antenna".into()))
+    }
+    fn get_tx_antenna(&self, _channel: usize) -> SDRResult<String> {
+        Err(SDRError::NotSupport("get_tx_antenna".into()))
+    }
+    fn tx_antennas(&self, _channel: usize) -> SDRResult<Vec<String>> {
+        Err(SDRError::NotSupport("tx_antennas".into()))
+    }
+    fn set_rx_antenna(&self, _antenna: &str, _channel: usize) -> SDRResult<()> {
+        Err(SDRError::NotSupport("set_rx_antenna".into()))
+    }
+    fn get_rx_antenna(&self, _channel: usize) -> SDRResult<String> {
+        Err(SDRError::NotSupport("get_rx_antenna".into()))
+    }
+    fn rx_antennas(&self, _channel: usize) -> SDRResult<Vec<String>> {
+        Err(SDRError::NotSupport("rx_antennas".into()))
+    }
+}
+
+/// Device clock, used for timed streaming and for aligning several radios to
+/// a shared PPS. `mboard` selects the motherboard on multi-board devices.
+pub trait DeviceTime {
+    fn time_now(&self, mboard: usize) -> SDRResult<TimeSpec>;
+    fn time_last_pps(&self, mboard: usize) -> SDRResult<TimeSpec>;
+    fn set_time_now(&self, time: TimeSpec, mboard: usize) -> SDRResult<()>;
+    /// Latch `time` into the clock on the next PPS edge.
+    fn set_time_next_pps(&self, time: TimeSpec, mboard: usize) -> SDRResult<()>;
+    /// Wait for a PPS edge, then set `time` on all boards at the following one.
+    fn set_time_unknown_pps(&self, time: TimeSpec) -> SDRResult<()>;
+    /// Whether the clocks of all boards agree.
+    fn time_synchronized(&self) -> SDRResult<bool>;
+}
+
+/// Per-stage and normalized gain. `SDRDevice::set_*_gain` spreads the
+/// overall gain across stages; these address a single stage by name, or map
+/// 0..=1 onto the channel's full gain range.
+pub trait GainControl {
+    fn tx_gain_names(&self, channel: usize) -> SDRResult<Vec<String>>;
+    fn set_tx_gain_stage(&self, gain: f64, name: &str, channel: usize) -> SDRResult<()>;
+    fn get_tx_gain_stage(&self, name: &str, channel: usize) -> SDRResult<f64>;
+    fn tx_gain_stage_range(&self, name: &str, channel: usize) -> SDRResult<MetaRange>;
+    fn set_normalized_tx_gain(&self, gain: f64, channel: usize) -> SDRResult<()>;
+    fn get_normalized_tx_gain(&self, channel: usize) -> SDRResult<f64>;
+    fn rx_gain_names(&self, channel: usize) -> SDRResult<Vec<String>>;
+    fn set_rx_gain_stage(&self, gain: f64, name: &str, channel: usize) -> SDRResult<()>;
+    fn get_rx_gain_stage(&self, name: &str, channel: usize) -> SDRResult<f64>;
+    fn rx_gain_stage_range(&self, name: &str, channel: usize) -> SDRResult<MetaRange>;
+    fn set_normalized_rx_gain(&self, gain: f64, channel: usize) -> SDRResult<()>;
+    fn get_normalized_rx_gain(&self, channel: usize) -> SDRResult<f64>;
+}
+
+/// Hardware RX front-end corrections. See `starsdr::dsp` for software
+/// equivalents on radios without them.
+pub trait FrontendCorrections {
+    fn set_rx_agc(&self, enable: bool, channel: usize) -> SDRResult<()>;
+    fn set_rx_dc_offset_enabled(&self, enable: bool, channel: usize) -> SDRResult<()>;
+    fn set_rx_iq_balance_enabled(&self, enable: bool, channel: usize) -> SDRResult<()>;
+}
+
+/// Per-LO control on front ends with more than one LO stage or with LO
+/// sharing between channels. `name` is one of `*_lo_names` or `ALL_LOS`.
+pub trait LoControl {
+    fn tx_lo_names(&self, channel: usize) -> SDRResult<Vec<String>>;
+    fn set_tx_lo_source(&self, source: &LoSource, name: &str, channel: usize) -> SDRResult<()>;
+    fn get_tx_lo_source(&self, name: &str, channel: usize) -> SDRResult<LoSource>;
+    fn tx_lo_sources(&self, name: &str, channel: usize) -> SDRResult<Vec<LoSource>>;
+    fn set_tx_lo_export_enabled(&self, enabled: bool, name: &str, channel: usize) -> SDRResult<()>;
+    fn get_tx_lo_export_enabled(&self, name: &str, channel: usize) -> SDRResult<bool>;
+    /// Returns the frequency the LO was actually set to.
+    fn set_tx_lo_freq(&self, freq: f64, name: &str, channel: usize) -> SDRResult<f64>;
+    fn get_tx_lo_freq(&self, name: &str, channel: usize) -> SDRResult<f64>;
+    fn rx_lo_names(&self, channel: usize) -> SDRResult<Vec<String>>;
+    fn set_rx_lo_source(&self, source: &LoSource, name: &str, channel: usize) -> SDRResult<()>;
+    fn get_rx_lo_source(&self, name: &str, channel: usize) -> SDRResult<LoSource>;
+    fn rx_lo_sources(&self, name: &str, channel: usize) -> SDRResult<Vec<LoSource>>;
+    fn set_rx_lo_export_enabled(&self, enabled: bool, name: &str, channel: usize) -> SDRResult<()>;
+    fn get_rx_lo_export_enabled(&self, name: &str, channel: usize) -> SDRResult<bool>;
+    /// Returns the frequency the LO was actually set to.
+    fn set_rx_lo_freq(&self, freq: f64, name: &str, channel: usize) -> SDRResult<f64>;
+    fn get_rx_lo_freq(&self, name: &str, channel: usize) -> SDRResult<f64>;
+}
+
+/// Front end to channel mapping on boards with several daughterboards.
+pub trait SubdevControl {
+    fn set_tx_subdev_spec(&self, spec: &SubdevSpec, mboard: usize) -> SDRResult<()>;
+    fn get_tx_subdev_spec(&self, mboard: usize) -> SDRResult<SubdevSpec>;
+    /// Human readable name of the front end behind `channel`.
+    fn tx_subdev_name(&self, channel: usize) -> SDRResult<String>;
+    fn set_rx_subdev_spec(&self, spec: &SubdevSpec, mboard: usize) -> SDRResult<()>;
+    fn get_rx_subdev_spec(&self, mboard: usize) -> SDRResult<SubdevSpec>;
+    fn rx_subdev_name(&self, channel: usize) -> SDRResult<String>;
+}
+
+/// Selects every motherboard in calls taking an `mboard` argument.
+pub const ALL_MBOARDS: usize = usize::MAX;
+
+/// Timed commands: after `set_command_time`, control calls such as tuning or
+/// gain changes are queued to take effect at `time` instead of immediately.
+pub trait CommandTime {
+    fn set_command_time(&self, time: TimeSpec, mboard: usize) -> SDRResult<()>;
+    fn clear_command_time(&self, mboard: usize) -> SDRResult<()>;
+}
+
+/// Reference clock and time (PPS) source selection, e.g. "internal",
+/// "external" or "gpsdo".
+pub trait ClockControl {
+    fn set_clock_source(&self, source: &str, mboard: usize) -> SDRResult<()>;
+    fn get_clock_source(&self, mboard: usize) -> SDRResult<String>;
+    fn clock_sources(&self, mboard: usize) -> SDRResult<Vec<String>>;
+    fn set_time_source(&self, source: &str, mboard: usize) -> SDRResult<()>;
+    fn get_time_source(&self, mboard: usize) -> SDRResult<String>;
+    fn time_sources(&self, mboard: usize) -> SDRResult<Vec<String>>;
+    /// Export the reference clock on the board's output connector.
+    fn set_clock_source_out(&self, enable: bool, mboard: usize) -> SDRResult<()>;
+    /// Export the PPS on the board's output connector.
+    fn set_time_source_out(&self, enable: bool, mboard: usize) -> SDRResult<()>;
+}
+
+/// Sensors of a board or front end, such as LO lock, reference lock or
+/// temperature.
+pub trait Sensors {
+    fn sensor_names(&self, location: SensorLocation) -> SDRResult<Vec<String>>;
+    fn sensor(&self, name: &str, location: SensorLocation) -> SDRResult<Sensor>;
+
+    /// Polls a boolean sensor such as `lo_locked` or `ref_locked` until it
+    /// reads true, failing with `TimeOut` after `timeout`.
+    fn wait_for_sensor(
+        &self,
+        name: &str,
+        location: SensorLocation,
+        timeout: Duration,
+    ) -> SDRResult<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let sensor = self.sensor(name, location)?;
+            match sensor.as_bool() {
+                Some(true) => return Ok(()),
+                Some(false) => {}
+                None => {
+                    return Err(SDRError::Param {
+                        key: "name".into(),
+                        value: name.into(),
+                        msg: format!("not a boolean sensor: {}", sensor),
+                    })
+                }
+            }
+            if Instant::now() >= deadline {
+                return Err(SDRError::TimeOut);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
 }
 
 pub trait CreateTx<I: Send, T: Tx<I>> {
@@ -38,14 +277,112 @@ pub trait CreateTx<I: Send, T: Tx<I>> {
 
 pub trait Tx<Item: Send>: Send
 {
-    fn send(&self, v: &[Complex<Item>]) -> SDRResult<usize>;
+    /// Number of channels in the stream, one buffer per channel.
+    fn channel_count(&self) -> usize;
+
+    /// Send one buffer per channel, all of the same length, with timing and
+    /// burst flags from `md`.
+    fn send_multi_with(&self, bufs: &[&[Complex<Item>]], md: &TxMetadata) -> SDRResult<usize>;
+
+    fn send_multi(&self, bufs: &[&[Complex<Item>]]) -> SDRResult<usize> {
+        self.send_multi_with(bufs, &TxMetadata::default())
+    }
+
+    fn send_with(&self, v: &[Complex<Item>], md: &TxMetadata) -> SDRResult<usize> {
+        self.send_multi_with(&[v], md)
+    }
+
+    fn send(&self, v: &[Complex<Item>]) -> SDRResult<usize> {
+        self.send_multi(&[v])
+    }
 }
 
-pub trait CreateRx<I: Send, T: Rx<I>> {
+pub trait CreateRx<I: Sample, T: Rx<I>> {
     fn rx_stream(&self, channels: &[usize]) -> SDRResult<T>;
 }
 
-pub trait Rx<Item: Send>: Send {
-    fn recv(&mut self) -> SDRResult<Vec<Complex<Item>>>;
+pub trait Rx<Item: Sample>: Send {
+    /// Number of channels in the stream, one buffer per channel.
+    fn channel_count(&self) -> usize;
+
+    /// Largest number of samples a single receive call can return.
+    fn sample_num_max(&self) -> usize;
+
+    /// Receive into one buffer per channel without allocating.
+    /// `RecvInfo::samples` tells how much of each buffer was filled.
+    fn recv_multi_into(&mut self, bufs: &mut [&mut [Complex<Item>]]) -> SDRResult<RecvInfo>;
+
+    fn recv_into(&mut self, buf: &mut [Complex<Item>]) -> SDRResult<RecvInfo> {
+        self.recv_multi_into(&mut [buf])
+    }
+
+    fn recv(&mut self) -> SDRResult<Vec<Complex<Item>>> {
+        let mut buf = vec![Complex::default(); self.sample_num_max()];
+        let info = self.recv_into(&mut buf)?;
+        buf.truncate(info.samples);
+        Ok(buf)
+    }
+
+    fn recv_with_metadata(&mut self) -> SDRResult<(Vec<Complex<Item>>, RxMetadata)> {
+        let mut buf = vec![Complex::default(); self.sample_num_max()];
+        let info = self.recv_into(&mut buf)?;
+        buf.truncate(info.samples);
+        Ok((buf, info.metadata))
+    }
+
+    fn recv_multi(&mut self) -> SDRResult<Vec<Vec<Complex<Item>>>> {
+        let mut bufs = vec![vec![Complex::default(); self.sample_num_max()]; self.channel_count()];
+        let mut slices: Vec<&mut [Complex<Item>]> = bufs.iter_mut().map(|b| b.as_mut_slice()).collect();
+        let info = self.recv_multi_into(&mut slices)?;
+        for b in bufs.iter_mut() {
+            b.truncate(info.samples);
+        }
+        Ok(bufs)
+    }
 }
 
+impl<Item: Send, T: Tx<Item> + ?Sized> Tx<Item> for Box<T> {
+    fn channel_count(&self) -> usize {
+        (**self).channel_count()
+    }
+
+    fn send_multi_with(&self, bufs: &[&[Complex<Item>]], md: &TxMetadata) -> SDRResult<usize> {
+        (**self).send_multi_with(bufs, md)
+    }
+}
+
+impl<Item: Sample, R: Rx<Item> + ?Sized> Rx<Item> for Box<R> {
+    fn channel_count(&self) -> usize {
+        (**self).channel_count()
+    }
+
+    fn sample_num_max(&self) -> usize {
+        (**self).sample_num_max()
+    }
+
+    fn recv_multi_into(&mut self, bufs: &mut [&mut [Complex<Item>]]) -> SDRResult<RecvInfo> {
+        (**self).recv_multi_into(bufs)
+    }
+}
+
+/// Checks that a multi-channel call got one buffer per stream channel.
+pub fn check_channel_buffers(channels: usize, buffers: usize) -> SDRResult<()> {
+    if channels != buffers {
+        return Err(SDRError::Param {
+            key: "bufs".into(),
+            value: format!("len()={}", buffers),
+            msg: format!("stream has {} channels", channels),
+        });
+    }
+    Ok(())
+}
+
+#[async_trait]
+pub trait AsyncTx<Item: Send>: Send {
+    async fn send(&mut self, v: Vec<Complex<Item>>) -> SDRResult<usize>;
+}
+
+#[async_trait]
+pub trait AsyncRx<Item: Send>: Send {
+    async fn recv(&mut self) -> SDRResult<Vec<Complex<Item>>>;
+}
@@ -0,0 +1,125 @@
+use crate::{SDRError, TimeSpec};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RxMetadata {
+    /// Device time of the first sample in the buffer, if the device has one.
+    pub time_spec: Option<TimeSpec>,
+    pub start_of_burst: bool,
+    pub end_of_burst: bool,
+    /// The packet did not fit the buffer; the rest comes with the next call.
+    pub more_fragments: bool,
+    /// Offset of this buffer in the fragmented packet, in samples.
+    pub fragment_offset: usize,
+    /// A packet was dropped between this buffer and the previous one.
+    pub out_of_sequence: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RecvInfo {
+    pub samples: usize,
+    pub metadata: RxMetadata,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TxMetadata {
+    /// Device time at which to transmit the first sample; `None` sends now.
+    pub time_spec: Option<TimeSpec>,
+    pub start_of_burst: bool,
+    pub end_of_burst: bool,
+}
+
+impl TxMetadata {
+    /// First buffer of a burst sent at device time `time`.
+    pub fn at(time: TimeSpec) -> Self {
+        Self {
+            time_spec: Some(time),
+            start_of_burst: true,
+            end_of_burst: false,
+        }
+    }
+
+    pub fn start_of_burst() -> Self {
+        Self {
+            start_of_burst: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn end_of_burst() -> Self {
+        Self {
+            end_of_burst: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Asynchronous report from the device about a TX stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TxEventKind {
+    /// A burst with `end_of_burst` was fully transmitted.
+    BurstAck,
+    /// The device ran out of samples between packets.
+    Underflow,
+    /// Packets were lost between host and device.
+    SequenceError,
+    /// A timed packet arrived after its `time_spec`.
+    TimeError,
+    /// The device ran out of samples inside a packet.
+    UnderflowInPacket,
+    /// Packets were lost inside a burst.
+    SequenceErrorInBurst,
+    UserPayload([u32; 4]),
+    Unknown(u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TxEvent {
+    pub channel: usize,
+    pub time_spec: Option<TimeSpec>,
+    pub kind: TxEventKind,
+}
+
+impl TxEvent {
+    /// The event as an error, or `None` for acknowledgements and payloads.
+    pub fn error(&self) -> Option<SDRError> {
+        match self.kind {
+            TxEventKind::Underflow | TxEventKind::UnderflowInPacket => Some(SDRError::Underflow),
+            TxEventKind::SequenceError | TxEventKind::SequenceErrorInBurst => {
+                Some(SDRError::SequenceError(self.channel))
+            }
+            TxEventKind::TimeError => Some(SDRError::LateCommand(self.channel)),
+            TxEventKind::Unknown(code) => Some(SDRError::Unknown(format!(
+                "TX event {} on channel {}",
+                code, self.channel
+            ))),
+            TxEventKind::BurstAck | TxEventKind::UserPayload(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tx_event_error() {
+        let event = |kind| TxEvent {
+            channel: 1,
+            time_spec: None,
+            kind,
+        };
+        assert!(event(TxEventKind::BurstAck).error().is_none());
+        assert!(matches!(
+            event(TxEventKind::UnderflowInPacket).error(),
+            Some(SDRError::Underflow)
+        ));
+        assert!(matches!(
+            event(TxEventKind::SequenceErrorInBurst).error(),
+            Some(SDRError::SequenceError(1))
+        ));
+        assert!(matches!(
+            event(TxEventKind::TimeError).error(),
+            Some(SDRError::LateCommand(1))
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+use std::fmt::Display;
+
+/// Values from `start` to `stop` inclusive, every `step`. A step of 0 means
+/// the range is continuous.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Range {
+    pub start: f64,
+    pub stop: f64,
+    pub step: f64,
+}
+
+impl Range {
+    pub fn new(start: f64, stop: f64, step: f64) -> Self {
+        Self { start, stop, step }
+    }
+
+    /// A range holding the single value `v`.
+    pub fn value(v: f64) -> Self {
+        Self::new(v, v, 0.0)
+    }
+
+    pub fn contains(&self, v: f64) -> bool {
+        self.start <= v && v <= self.stop
+    }
+
+    /// Clamp `v` into the range, rounding to the nearest step if `clip_step`.
+    pub fn clip(&self, v: f64, clip_step: bool) -> f64 {
+        let v = v.clamp(self.start, self.stop);
+        if clip_step && self.step > 0.0 {
+            let v = ((v - self.start) / self.step).round() * self.step + self.start;
+            v.min(self.stop)
+        } else {
+            v
+        }
+    }
+}
+
+impl Display for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.start == self.stop {
+            write!(f, "({})", self.start)
+        } else if self.step == 0.0 {
+            write!(f, "({}, {})", self.start, self.stop)
+        } else {
+            write!(f, "({}, {}, {})", self.start, self.stop, self.step)
+        }
+    }
+}
+
+/// Ascending, non-overlapping ranges, like the gain or frequency ranges a
+/// device reports. Discrete sets such as supported rates are one `Range::value`
+/// per entry.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MetaRange(pub Vec<Range>);
+
+impl MetaRange {
+    pub fn new(ranges: Vec<Range>) -> Self {
+        Self(ranges)
+    }
+
+    pub fn ranges(&self) -> &[Range] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn start(&self) -> Option<f64> {
+        self.0.first().map(|r| r.start)
+    }
+
+    pub fn stop(&self) -> Option<f64> {
+        self.0.last().map(|r| r.stop)
+    }
+
+    /// Smallest non-zero step, or 0 if every range is continuous.
+    pub fn step(&self) -> f64 {
+        self.0
+            .iter()
+            .map(|r| r.step)
+            .filter(|s| *s > 0.0)
+            .reduce(f64::min)
+            .unwrap_or(0.0)
+    }
+
+    pub fn contains(&self, v: f64) -> bool {
+        self.0.iter().any(|r| r.contains(v))
+    }
+
+    /// Nearest value the device accepts: values between two ranges go to
+    /// the closer bound, and with `clip_step` values snap to the step grid.
+    /// An empty range returns `v` unchanged.
+    pub fn clip(&self, v: f64, clip_step: bool) -> f64 {
+        let mut last_stop = match self.0.first() {
+            Some(r) => r.stop,
+            None => return v,
+        };
+        for r in &self.0 {
+            if v < r.start {
+                return if (v - r.start).abs() < (v - last_stop).abs() {
+                    r.start
+                } else {
+                    last_stop
+                };
+            }
+            if v <= r.stop {
+                return r.clip(v, clip_step);
+            }
+            last_stop = r.stop;
+        }
+        last_stop
+    }
+}
+
+impl From<Range> for MetaRange {
+    fn from(r: Range) -> Self {
+        Self(vec![r])
+    }
+}
+
+impl From<Vec<Range>> for MetaRange {
+    fn from(v: Vec<Range>) -> Self {
+        Self(v)
+    }
+}
+
+impl Display for MetaRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, r) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", r)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clip() {
+        let r = MetaRange::new(vec![
+            Range::new(0.0, 10.0, 0.5),
+            Range::new(20.0, 30.0, 0.0),
+        ]);
+        assert_eq!(0.0, r.clip(-5.0, true));
+        assert_eq!(3.5, r.clip(3.4, true));
+        assert_eq!(3.4, r.clip(3.4, false));
+        assert_eq!(10.0, r.clip(12.0, true));
+        assert_eq!(20.0, r.clip(18.0, true));
+        assert_eq!(25.3, r.clip(25.3, true));
+        assert_eq!(30.0, r.clip(99.0, true));
+        assert_eq!(7.0, MetaRange::default().clip(7.0, true));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let rates: MetaRange = vec![Range::value(1e6), Range::value(2e6), Range::value(4e6)].into();
+        assert_eq!(Some(1e6), rates.start());
+        assert_eq!(Some(4e6), rates.stop());
+        assert_eq!(0.0, rates.step());
+        assert!(rates.contains(2e6));
+        assert!(!rates.contains(3e6));
+        assert_eq!(4e6, rates.clip(3.5e6, false));
+        assert_eq!("(1000000), (2000000), (4000000)", rates.to_string());
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    fmt::Display,
+    io::{ErrorKind, Read},
+};
+
+use num::Complex;
+
+use crate::SDRResult;
+
+/// Samples per call for streams without a transport limit of their own,
+/// the default packet size of a USB B2xx USRP.
+pub const SAMPLE_NUM_MAX: usize = 2040;
+
+pub trait Sample: Send + Copy + Default + 'static {
+    const FORMAT: SampleFormat;
+    fn to_f64(self) -> f64;
+    fn from_f64(v: f64) -> Self;
+}
+
+impl Sample for i16 {
+    const FORMAT: SampleFormat = SampleFormat::Sc16;
+
+    fn to_f64(self) -> f64 {
+        self as f64 / i16::MAX as f64
+    }
+
+    fn from_f64(v: f64) -> Self {
+        (v * i16::MAX as f64)
+            .round()
+            .clamp(i16::MIN as f64, i16::MAX as f64) as i16
+    }
+}
+
+impl Sample for f32 {
+    const FORMAT: SampleFormat = SampleFormat::Fc32;
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(v: f64) -> Self {
+        v as f32
+    }
+}
+
+impl Sample for f64 {
+    const FORMAT: SampleFormat = SampleFormat::Fc64;
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn from_f64(v: f64) -> Self {
+        v
+    }
+}
+
+/// Complex sample layout, named after the UHD host formats. The byte
+/// representation is interleaved little-endian I/Q.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SampleFormat {
+    Sc16,
+    Fc32,
+    Fc64,
+}
+
+impl SampleFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sc16" | "cs16" => Some(Self::Sc16),
+            "fc32" | "cf32" => Some(Self::Fc32),
+            "fc64" | "cf64" => Some(Self::Fc64),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sc16 => "sc16",
+            Self::Fc32 => "fc32",
+            Self::Fc64 => "fc64",
+        }
+    }
+
+    /// Bytes per complex sample.
+    pub fn sample_size(&self) -> usize {
+        match self {
+            Self::Sc16 => 4,
+            Self::Fc32 => 8,
+            Self::Fc64 => 16,
+        }
+    }
+
+    /// Decode whole samples from `bytes` into `out`, returning how many were
+    /// written.
+    pub fn decode<T: Sample>(&self, bytes: &[u8], out: &mut [Complex<T>]) -> usize {
+        let half = self.sample_size() / 2;
+        let mut n = 0;
+        for (s, o) in bytes.chunks_exact(self.sample_size()).zip(out.iter_mut()) {
+            let (re, im) = s.split_at(half);
+            *o = Complex::new(self.decode_one(re), self.decode_one(im));
+            n += 1;
+        }
+        n
+    }
+
+    /// Fill `out` from `reader`, using `scratch` for the raw bytes. Returns
+    /// fewer than `out.len()` samples only at end of input.
+    pub fn read<T: Sample, R: Read>(
+        &self,
+        reader: &mut R,
+        scratch: &mut Vec<u8>,
+        out: &mut [Complex<T>],
+    ) -> SDRResult<usize> {
+        let size = self.sample_size();
+        scratch.resize(out.len() * size, 0);
+        let mut n = 0;
+        while n < scratch.len() {
+            match reader.read(&mut scratch[n..]) {
+                Ok(0) => break,
+                Ok(r) => n += r,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(self.decode(&scratch[..n / size * size], out))
+    }
+
+    fn decode_one<T: Sample>(&self, b: &[u8]) -> T {
+        match self {
+            Self::Sc16 => T::from_f64(i16::from_le_bytes(b.try_into().unwrap()).to_f64()),
+            Self::Fc32 => T::from_f64(f32::from_le_bytes(b.try_into().unwrap()).to_f64()),
+            Self::Fc64 => T::from_f64(f64::from_le_bytes(b.try_into().unwrap())),
+        }
+    }
+
+    pub fn encode<T: Sample>(&self, v: &[Complex<T>], out: &mut Vec<u8>) {
+        for s in v {
+            self.encode_one(s.re, out);
+            self.encode_one(s.im, out);
+        }
+    }
+
+    fn encode_one<T: Sample>(&self, v: T, out: &mut Vec<u8>) {
+        let v = v.to_f64();
+        match self {
+            Self::Sc16 => out.extend_from_slice(&i16::from_f64(v).to_le_bytes()),
+            Self::Fc32 => out.extend_from_slice(&f32::from_f64(v).to_le_bytes()),
+            Self::Fc64 => out.extend_from_slice(&v.to_le_bytes()),
+        }
+    }
+}
+
+impl Display for SampleFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt::Display;
+
+/// Where a sensor lives: a motherboard, or the front end of a channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SensorLocation {
+    Mboard(usize),
+    Rx(usize),
+    Tx(usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SensorValue {
+    Bool(bool),
+    Int(i64),
+    Real(f64),
+    String(String),
+}
+
+impl Display for SensorValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(v) => write!(f, "{}", v),
+            Self::Int(v) => write!(f, "{}", v),
+            Self::Real(v) => write!(f, "{}", v),
+            Self::String(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// One reading, e.g. `lo_locked: true` or `temp: 45.5 C`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sensor {
+    pub name: String,
+    pub unit: String,
+    pub value: SensorValue,
+}
+
+impl Sensor {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.value {
+            SensorValue::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Integer and real values as `f64`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.value {
+            SensorValue::Int(v) => Some(v as f64),
+            SensorValue::Real(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Sensor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.value)?;
+        if !self.unit.is_empty() && !matches!(self.value, SensorValue::Bool(_)) {
+            write!(f, " {}", self.unit)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, time::Duration};
+
+    use super::*;
+    use crate::{SDRError, SDRResult, Sensors};
+
+    /// Locks after a number of reads.
+    struct Pll {
+        reads: Cell<u32>,
+        lock_after: u32,
+    }
+
+    impl Sensors for Pll {
+        fn sensor_names(&self, _location: SensorLocation) -> SDRResult<Vec<String>> {
+            Ok(vec!["lo_locked".into(), "temp".into()])
+        }
+
+        fn sensor(&self, name: &str, _location: SensorLocation) -> SDRResult<Sensor> {
+            self.reads.set(self.reads.get() + 1);
+            let value = match name {
+                "lo_locked" => SensorValue::Bool(self.reads.get() > self.lock_after),
+                "temp" => SensorValue::Real(45.5),
+                _ => return Err(SDRError::NotFound),
+            };
+            Ok(Sensor {
+                name: name.into(),
+                unit: "C".into(),
+                value,
+            })
+        }
+    }
+
+    #[test]
+    fn test_wait_for_sensor() {
+        let pll = Pll {
+            reads: Cell::new(0),
+            lock_after: 3,
+        };
+        let rx0 = SensorLocation::Rx(0);
+        pll.wait_for_sensor("lo_locked", rx0, Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(4, pll.reads.get());
+
+        let never = Pll {
+            reads: Cell::new(0),
+            lock_after: u32::MAX,
+        };
+        let r = never.wait_for_sensor("lo_locked", rx0, Duration::from_millis(30));
+        assert!(matches!(r, Err(SDRError::TimeOut)));
+
+        let r = pll.wait_for_sensor("temp", rx0, Duration::from_secs(1));
+        assert!(matches!(r, Err(SDRError::Param { .. })));
+        assert_eq!("temp: 45.5 C", pll.sensor("temp", rx0).unwrap().to_string());
+    }
+}
@@ -0,0 +1,99 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::SDRError;
+
+/// One front end: daughterboard slot and subdevice on it, e.g. `A:0`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubdevPair {
+    pub db_name: String,
+    pub sd_name: String,
+}
+
+impl SubdevPair {
+    pub fn new(db_name: &str, sd_name: &str) -> Self {
+        Self {
+            db_name: db_name.into(),
+            sd_name: sd_name.into(),
+        }
+    }
+}
+
+impl Display for SubdevPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.sd_name.is_empty() {
+            write!(f, "{}", self.db_name)
+        } else {
+            write!(f, "{}:{}", self.db_name, self.sd_name)
+        }
+    }
+}
+
+/// Maps a board's front ends to channels, in channel order. Parses and
+/// prints the UHD markup: `"A:0 B:0"` makes slot A channel 0 and slot B
+/// channel 1.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SubdevSpec(pub Vec<SubdevPair>);
+
+impl SubdevSpec {
+    pub fn pairs(&self) -> &[SubdevPair] {
+        &self.0
+    }
+}
+
+impl FromStr for SubdevSpec {
+    type Err = SDRError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pairs = s
+            .split_whitespace()
+            .map(|p| {
+                let mut parts = p.split(':');
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(db), sd, None) if !db.is_empty() => {
+                        Ok(SubdevPair::new(db, sd.unwrap_or("")))
+                    }
+                    _ => Err(SDRError::Param {
+                        key: "subdev".into(),
+                        value: s.into(),
+                        msg: format!("invalid pair `{}`, expected db:sd", p),
+                    }),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self(pairs))
+    }
+}
+
+impl Display for SubdevSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, p) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", p)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let spec: SubdevSpec = "A:0  B:0".parse().unwrap();
+        assert_eq!(
+            vec![SubdevPair::new("A", "0"), SubdevPair::new("B", "0")],
+            spec.0
+        );
+        assert_eq!("A:0 B:0", spec.to_string());
+
+        let spec: SubdevSpec = "A:AB".parse().unwrap();
+        assert_eq!("AB", spec.pairs()[0].sd_name);
+
+        assert_eq!(SubdevSpec::default(), "".parse().unwrap());
+        assert!("A:0:1".parse::<SubdevSpec>().is_err());
+        assert!(":0".parse::<SubdevSpec>().is_err());
+    }
+}
@@ -0,0 +1,124 @@
+use std::{
+    fmt::Display,
+    ops::{Add, Sub},
+};
+
+/// A device time as whole seconds plus a fractional part in `[0, 1)`,
+/// matching UHD's `time_spec_t`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct TimeSpec {
+    pub full_secs: i64,
+    pub frac_secs: f64,
+}
+
+impl TimeSpec {
+    pub fn new(full_secs: i64, frac_secs: f64) -> Self {
+        let carry = frac_secs.floor();
+        Self {
+            full_secs: full_secs + carry as i64,
+            frac_secs: frac_secs - carry,
+        }
+    }
+
+    pub fn from_secs(secs: f64) -> Self {
+        Self::new(0, secs)
+    }
+
+    /// Time of sample `index` in a stream running at `rate` that started at
+    /// zero, computed without losing precision on long runs.
+    pub fn from_samples(index: u64, rate: f64) -> Self {
+        if rate >= 1.0 && rate.fract() == 0.0 && rate < u64::MAX as f64 {
+            let rate_int = rate as u64;
+            return Self::new((index / rate_int) as i64, (index % rate_int) as f64 / rate);
+        }
+        let full = (index as f64 / rate).floor();
+        // Rounding can put `full * rate` just past `index`.
+        let rest = index.saturating_sub((full * rate).round() as u64);
+        Self::new(full as i64, rest as f64 / rate)
+    }
+
+    pub fn as_secs(&self) -> f64 {
+        self.full_secs as f64 + self.frac_secs
+    }
+}
+
+impl Add for TimeSpec {
+    type Output = TimeSpec;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.full_secs + rhs.full_secs,
+            self.frac_secs + rhs.frac_secs,
+        )
+    }
+}
+
+impl Sub for TimeSpec {
+    type Output = TimeSpec;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.full_secs - rhs.full_secs,
+            self.frac_secs - rhs.frac_secs,
+        )
+    }
+}
+
+impl Display for TimeSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.9}s", self.as_secs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            TimeSpec {
+                full_secs: 3,
+                frac_secs: 0.5
+            },
+            TimeSpec::from_secs(3.5)
+        );
+        assert_eq!(
+            TimeSpec {
+                full_secs: -2,
+                frac_secs: 0.75
+            },
+            TimeSpec::new(-1, -0.25)
+        );
+        let t = TimeSpec::new(10, 0.75) + TimeSpec::new(1, 0.5);
+        assert_eq!(
+            TimeSpec {
+                full_secs: 12,
+                frac_secs: 0.25
+            },
+            t
+        );
+        assert_eq!(
+            TimeSpec {
+                full_secs: 9,
+                frac_secs: 0.5
+            },
+            t - TimeSpec::new(2, 0.75)
+        );
+    }
+
+    #[test]
+    fn test_from_samples() {
+        let t = TimeSpec::from_samples(200_000_000 * 3600 + 1, 200e6);
+        assert_eq!(3600, t.full_secs);
+        assert_eq!(5e-9, t.frac_secs);
+
+        let index = (1u64 << 53) + 3;
+        let t = TimeSpec::from_samples(index, 0.3);
+        assert!((0.0..1.0).contains(&t.frac_secs));
+        assert!((t.as_secs() / (index as f64 / 0.3) - 1.0).abs() < 1e-12);
+
+        let t = TimeSpec::from_samples(u64::MAX, 30.72e6 / 7.0);
+        assert!((0.0..1.0).contains(&t.frac_secs));
+    }
+}
@@ -0,0 +1,143 @@
+use std::fmt::Display;
+
+/// How a tunable stage picks its frequency.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TunePolicy {
+    /// Leave the stage alone.
+    None,
+    /// Let the driver choose.
+    #[default]
+    Auto,
+    /// Use the frequency given in the request.
+    Manual,
+}
+
+/// Where to put the RF LO and the DSP (CORDIC) shift for a tune.
+///
+/// `TuneRequest::new(f)` lets the driver split `f` between the stages. To
+/// keep the LO leakage out of band, offset the LO and let DSP make up the
+/// difference:
+///
+/// ```
+/// # use starsdr_interface::TuneRequest;
+/// let req = TuneRequest::new(2.4e9).lo_offset(5e6);
+/// assert_eq!(2.405e9, req.rf_freq);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TuneRequest {
+    pub target_freq: f64,
+    pub rf_freq_policy: TunePolicy,
+    pub rf_freq: f64,
+    pub dsp_freq_policy: TunePolicy,
+    pub dsp_freq: f64,
+    /// Driver specific options, e.g. "mode_n=integer".
+    pub args: String,
+}
+
+impl TuneRequest {
+    pub fn new(target_freq: f64) -> Self {
+        Self {
+            target_freq,
+            rf_freq_policy: TunePolicy::Auto,
+            rf_freq: 0.0,
+            dsp_freq_policy: TunePolicy::Auto,
+            dsp_freq: 0.0,
+            args: String::new(),
+        }
+    }
+
+    /// Tune the LO to `target_freq + offset`.
+    pub fn lo_offset(mut self, offset: f64) -> Self {
+        self.rf_freq_policy = TunePolicy::Manual;
+        self.rf_freq = self.target_freq + offset;
+        self
+    }
+
+    pub fn rf_freq(mut self, freq: f64) -> Self {
+        self.rf_freq_policy = TunePolicy::Manual;
+        self.rf_freq = freq;
+        self
+    }
+
+    pub fn dsp_freq(mut self, freq: f64) -> Self {
+        self.dsp_freq_policy = TunePolicy::Manual;
+        self.dsp_freq = freq;
+        self
+    }
+
+    pub fn rf_policy(mut self, policy: TunePolicy) -> Self {
+        self.rf_freq_policy = policy;
+        self
+    }
+
+    pub fn dsp_policy(mut self, policy: TunePolicy) -> Self {
+        self.dsp_freq_policy = policy;
+        self
+    }
+
+    pub fn args(mut self, args: &str) -> Self {
+        self.args = args.into();
+        self
+    }
+}
+
+/// What the device actually tuned to.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TuneResult {
+    /// Requested RF frequency clipped to the front end's range.
+    pub clipped_rf_freq: f64,
+    pub target_rf_freq: f64,
+    pub actual_rf_freq: f64,
+    pub target_dsp_freq: f64,
+    pub actual_dsp_freq: f64,
+}
+
+/// LO name that addresses every LO of a channel at once.
+pub const ALL_LOS: &str = "all";
+
+/// Where a front end takes its LO from. Sharing one LO between channels, by
+/// exporting it from one and importing it on the others, keeps them phase
+/// coherent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoSource {
+    Internal,
+    External,
+    /// The LO of the neighbouring channel on the same daughterboard.
+    Companion,
+    /// An exported LO routed back into the same board.
+    Reimport,
+    Disabled,
+    Other(String),
+}
+
+impl LoSource {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Internal => "internal",
+            Self::External => "external",
+            Self::Companion => "companion",
+            Self::Reimport => "reimport",
+            Self::Disabled => "disabled",
+            Self::Other(s) => s,
+        }
+    }
+}
+
+impl From<&str> for LoSource {
+    fn from(name: &str) -> Self {
+        match name {
+            "internal" => Self::Internal,
+            "external" => Self::External,
+            "companion" => Self::Companion,
+            "reimport" => Self::Reimport,
+            "disabled" => Self::Disabled,
+            other => Self::Other(other.into()),
+        }
+    }
+}
+
+impl Display for LoSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
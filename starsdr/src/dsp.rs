@@ -0,0 +1,242 @@
+//! Software front-end corrections for radios without hardware DC offset or
+//! IQ balance correction. Each block wraps an `Rx` and is itself an `Rx`, so
+//! they stack: `IqBalance::new(DcBlocker::new(rx, 1e-3), 1e-4)`.
+
+use std::marker::PhantomData;
+
+use starsdr_interface::*;
+
+type C64 = Complex<f64>;
+
+/// Removes the DC component by subtracting a running mean per channel.
+/// `alpha` is the mean's update weight per sample; smaller values track more
+/// slowly but notch less of the signal around 0 Hz.
+pub struct DcBlocker<T: Sample, R: Rx<T>> {
+    inner: R,
+    alpha: f64,
+    mean: Vec<C64>,
+    _t: PhantomData<T>,
+}
+
+impl<T: Sample, R: Rx<T>> DcBlocker<T, R> {
+    pub fn new(inner: R, alpha: f64) -> Self {
+        let mean = vec![C64::default(); inner.channel_count()];
+        Self {
+            inner,
+            alpha,
+            mean,
+            _t: PhantomData,
+        }
+    }
+
+    /// Current DC estimate per channel.
+    pub fn offset(&self) -> &[C64] {
+        &self.mean
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<T: Sample, R: Rx<T>> Rx<T> for DcBlocker<T, R> {
+    fn channel_count(&self) -> usize {
+        self.inner.channel_count()
+    }
+
+    fn sample_num_max(&self) -> usize {
+        self.inner.sample_num_max()
+    }
+
+    fn recv_multi_into(&mut self, bufs: &mut [&mut [Complex<T>]]) -> SDRResult<RecvInfo> {
+        let info = self.inner.recv_multi_into(bufs)?;
+        for (buf, mean) in bufs.iter_mut().zip(self.mean.iter_mut()) {
+            for s in buf[..info.samples].iter_mut() {
+                let x = to_c64(*s);
+                *mean += (x - *mean) * self.alpha;
+                *s = from_c64(x - *mean);
+            }
+        }
+        Ok(info)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct IqStats {
+    /// E[I²]
+    ii: f64,
+    /// E[Q²]
+    qq: f64,
+    /// E[I·Q]
+    iq: f64,
+}
+
+/// Blind IQ imbalance correction. Per channel it tracks the I and Q powers
+/// and their correlation, removes the part of Q correlated with I (phase
+/// skew) and rescales Q to the power of I (gain mismatch). Assumes the input
+/// has no DC offset, so put it after a `DcBlocker` when in doubt.
+pub struct IqBalance<T: Sample, R: Rx<T>> {
+    inner: R,
+    alpha: f64,
+    stats: Vec<IqStats>,
+    _t: PhantomData<T>,
+}
+
+impl<T: Sample, R: Rx<T>> IqBalance<T, R> {
+    pub fn new(inner: R, alpha: f64) -> Self {
+        let stats = vec![IqStats::default(); inner.channel_count()];
+        Self {
+            inner,
+            alpha,
+            stats,
+            _t: PhantomData,
+        }
+    }
+
+    /// Estimated (gain, phase) imbalance per channel: the Q/I amplitude ratio
+    /// and the skew from quadrature in radians.
+    pub fn imbalance(&self) -> Vec<(f64, f64)> {
+        self.stats
+            .iter()
+            .map(|s| {
+                if s.ii <= 0.0 || s.qq <= 0.0 {
+                    return (1.0, 0.0);
+                }
+                let gain = (s.qq / s.ii).sqrt();
+                let phase = (s.iq / (s.ii * s.qq).sqrt()).clamp(-1.0, 1.0).asin();
+                (gain, phase)
+            })
+            .collect()
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<T: Sample, R: Rx<T>> Rx<T> for IqBalance<T, R> {
+    fn channel_count(&self) -> usize {
+        self.inner.channel_count()
+    }
+
+    fn sample_num_max(&self) -> usize {
+        self.inner.sample_num_max()
+    }
+
+    fn recv_multi_into(&mut self, bufs: &mut [&mut [Complex<T>]]) -> SDRResult<RecvInfo> {
+        let info = self.inner.recv_multi_into(bufs)?;
+        let a = self.alpha;
+        for (buf, st) in bufs.iter_mut().zip(self.stats.iter_mut()) {
+            for s in buf[..info.samples].iter_mut() {
+                let x = to_c64(*s);
+                st.ii += (x.re * x.re - st.ii) * a;
+                st.qq += (x.im * x.im - st.qq) * a;
+                st.iq += (x.re * x.im - st.iq) * a;
+                if st.ii <= 0.0 {
+                    continue;
+                }
+                let skew = st.iq / st.ii;
+                let q_power = st.qq - skew * st.iq;
+                if q_power <= 0.0 {
+                    continue;
+                }
+                let q = (x.im - skew * x.re) * (st.ii / q_power).sqrt();
+                *s = from_c64(C64::new(x.re, q));
+            }
+        }
+        Ok(info)
+    }
+}
+
+fn to_c64<T: Sample>(s: Complex<T>) -> C64 {
+    C64::new(s.re.to_f64(), s.im.to_f64())
+}
+
+fn from_c64<T: Sample>(s: C64) -> Complex<T> {
+    Complex::new(T::from_f64(s.re), T::from_f64(s.im))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+
+    /// Endless tone at 1/16 of the sample rate with a fixed DC offset and
+    /// IQ imbalance.
+    struct Tone {
+        n: usize,
+        dc: C64,
+        gain: f64,
+        phase: f64,
+    }
+
+    impl Rx<f32> for Tone {
+        fn channel_count(&self) -> usize {
+            1
+        }
+
+        fn sample_num_max(&self) -> usize {
+            1024
+        }
+
+        fn recv_multi_into(&mut self, bufs: &mut [&mut [Complex<f32>]]) -> SDRResult<RecvInfo> {
+            check_channel_buffers(1, bufs.len())?;
+            let len = bufs[0].len().min(self.sample_num_max());
+            for s in bufs[0][..len].iter_mut() {
+                let w = 2.0 * PI * self.n as f64 / 16.0;
+                let x = C64::new(w.cos(), self.gain * (w + self.phase).sin()) + self.dc;
+                *s = from_c64(x);
+                self.n += 1;
+            }
+            Ok(RecvInfo {
+                samples: len,
+                ..Default::default()
+            })
+        }
+    }
+
+    fn settle<R: Rx<f32>>(rx: &mut R) -> Vec<Complex<f32>> {
+        for _ in 0..20 {
+            rx.recv().unwrap();
+        }
+        rx.recv().unwrap()
+    }
+
+    #[test]
+    fn test_dc_blocker() {
+        let tone = Tone {
+            n: 0,
+            dc: C64::new(0.3, -0.2),
+            gain: 1.0,
+            phase: 0.0,
+        };
+        let mut rx = DcBlocker::new(tone, 1e-3);
+        let out = settle(&mut rx);
+
+        let mean = out.iter().fold(Complex::new(0f32, 0.0), |a, s| a + s) / out.len() as f32;
+        assert!(mean.norm() < 0.01, "residual dc {}", mean);
+        assert!((rx.offset()[0] - C64::new(0.3, -0.2)).norm() < 0.01);
+    }
+
+    #[test]
+    fn test_iq_balance() {
+        let tone = Tone {
+            n: 0,
+            dc: C64::default(),
+            gain: 0.8,
+            phase: 0.1,
+        };
+        let mut rx = IqBalance::new(tone, 1e-3);
+        let out = settle(&mut rx);
+
+        let (gain, phase) = rx.imbalance()[0];
+        assert!((gain - 0.8).abs() < 0.01, "gain {}", gain);
+        assert!((phase - 0.1).abs() < 0.01, "phase {}", phase);
+
+        // A balanced tone has constant magnitude.
+        for s in out {
+            assert!((s.norm() - 1.0).abs() < 0.02, "{}", s.norm());
+        }
+    }
+}
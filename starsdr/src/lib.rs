@@ -2,6 +2,20 @@ pub use num::complex::Complex64;
 pub use starsdr_interface::*;
 #[cfg(feature = "driver-uhd")]
 pub use starsdr_uhd::*;
+#[cfg(feature = "driver-mock")]
+pub use starsdr_mock::*;
+#[cfg(feature = "driver-file")]
+pub use starsdr_file::*;
+
+pub mod dsp;
+pub mod registry;
+pub use registry::DriverRegistry;
+mod timed;
+pub use timed::{AtTime, CommandTimeGuard};
+#[cfg(feature = "sigmf")]
+pub mod sigmf;
+#[cfg(feature = "async")]
+pub mod stream;
 
 pub struct SDR<D>
 where
@@ -19,9 +33,31 @@ impl<D: SDRDriver> SDR<D> {
     pub fn device_list(&self) -> SDRResult<Vec<D::Item>> {
         self.driver.list()
     }
+
+    pub fn device_list_with(&self, filter: &DeviceFilter) -> SDRResult<Vec<D::Item>> {
+        self.driver.list_with(filter)
+    }
+}
+
+#[cfg(all(test, feature = "driver-mock"))]
+mod mock_tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_loopback() {
+        let sdr = SDR::new(DriverMock::new());
+        let mut d = sdr.device_list().unwrap().pop().unwrap();
+        d.open().unwrap();
+
+        let tx: TxMock<f32> = d.tx_stream(&[0]).unwrap();
+        let mut rx: RxMock<f32> = d.rx_stream(&[0]).unwrap();
+        let data = vec![Complex::new(0.5f32, -0.5); 16];
+        tx.send(&data).unwrap();
+        assert_eq!(data, rx.recv().unwrap());
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "driver-uhd"))]
 mod tests {
     use log::debug;
 
@@ -97,7 +133,7 @@ mod tests {
         debug!("max: {}", c);
 
         for _ in 0..1000 {
-            let n = tx.send(data.as_slice()).unwrap();
+            let n = tx.send_multi(&[data.as_slice(), data.as_slice()]).unwrap();
             debug!("send: {}", n);
         }
     }
@@ -127,7 +163,7 @@ mod tests {
         debug!("max: {}", c);
 
         for _ in 0..1000 {
-            let n = tx.send(data.as_slice()).unwrap();
+            let n = tx.send_multi(&[data.as_slice(), data.as_slice()]).unwrap();
             debug!("send: {}", n);
         }
     }
@@ -0,0 +1,324 @@
+//! Runtime driver selection. `SDR<D>` is tied to one driver type; a
+//! `DriverRegistry` holds any number of them behind trait objects, so one
+//! application can list and open devices from every compiled-in backend.
+
+use std::any::Any;
+
+use log::warn;
+#[cfg(feature = "driver-file")]
+use starsdr_file::{RxFile, TxFile};
+use starsdr_interface::*;
+#[cfg(feature = "driver-mock")]
+use starsdr_mock::{RxMock, TxMock};
+#[cfg(feature = "driver-uhd")]
+use starsdr_uhd::{RxUHD, TxUHD};
+
+/// An `SDRDevice` whose streams are boxed, so it can be used without
+/// knowing the driver. `as_any` gives back the concrete device for driver
+/// specific APIs.
+pub trait DynDevice: SDRDevice {
+    fn as_any(&self) -> &dyn Any;
+    fn tx_stream_f32(&self, channels: &[usize]) -> SDRResult<Box<dyn Tx<f32>>>;
+    fn tx_stream_i16(&self, channels: &[usize]) -> SDRResult<Box<dyn Tx<i16>>>;
+    fn tx_stream_f64(&self, channels: &[usize]) -> SDRResult<Box<dyn Tx<f64>>>;
+    fn rx_stream_f32(&self, channels: &[usize]) -> SDRResult<Box<dyn Rx<f32>>>;
+    fn rx_stream_i16(&self, channels: &[usize]) -> SDRResult<Box<dyn Rx<i16>>>;
+    fn rx_stream_f64(&self, channels: &[usize]) -> SDRResult<Box<dyn Rx<f64>>>;
+}
+
+#[cfg(any(
+    feature = "driver-uhd",
+    feature = "driver-mock",
+    feature = "driver-file"
+))]
+macro_rules! impl_dyn_device {
+    ($device:ty, $tx:ident, $rx:ident) => {
+        impl DynDevice for $device {
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+
+            fn tx_stream_f32(&self, channels: &[usize]) -> SDRResult<Box<dyn Tx<f32>>> {
+                let tx: $tx<f32> = self.tx_stream(channels)?;
+                Ok(Box::new(tx))
+            }
+
+            fn tx_stream_i16(&self, channels: &[usize]) -> SDRResult<Box<dyn Tx<i16>>> {
+                let tx: $tx<i16> = self.tx_stream(channels)?;
+                Ok(Box::new(tx))
+            }
+
+            fn tx_stream_f64(&self, channels: &[usize]) -> SDRResult<Box<dyn Tx<f64>>> {
+                let tx: $tx<f64> = self.tx_stream(channels)?;
+                Ok(Box::new(tx))
+            }
+
+            fn rx_stream_f32(&self, channels: &[usize]) -> SDRResult<Box<dyn Rx<f32>>> {
+                let rx: $rx<f32> = self.rx_stream(channels)?;
+                Ok(Box::new(rx))
+            }
+
+            fn rx_stream_i16(&self, channels: &[usize]) -> SDRResult<Box<dyn Rx<i16>>> {
+                let rx: $rx<i16> = self.rx_stream(channels)?;
+                Ok(Box::new(rx))
+            }
+
+            fn rx_stream_f64(&self, channels: &[usize]) -> SDRResult<Box<dyn Rx<f64>>> {
+                let rx: $rx<f64> = self.rx_stream(channels)?;
+                Ok(Box::new(rx))
+            }
+        }
+    };
+}
+
+#[cfg(feature = "driver-uhd")]
+impl_dyn_device!(starsdr_uhd::DeviceUHD, TxUHD, RxUHD);
+#[cfg(feature = "driver-mock")]
+impl_dyn_device!(starsdr_mock::DeviceMock, TxMock, RxMock);
+#[cfg(feature = "driver-file")]
+impl_dyn_device!(starsdr_file::DeviceFile, TxFile, RxFile);
+
+/// Object safe counterpart of `SDRDriver`.
+pub trait DynDriver: Send {
+    fn name(&self) -> &'static str;
+    fn list(&self, filter: &DeviceFilter) -> SDRResult<Vec<Box<dyn DynDevice>>>;
+    fn open(&self, args: &DeviceArgs) -> SDRResult<Box<dyn DynDevice>>;
+}
+
+struct Driver<D>(D);
+
+impl<D: SDRDriver> DynDriver for Driver<D>
+where
+    D::Item: DynDevice + 'static,
+{
+    fn name(&self) -> &'static str {
+        D::NAME
+    }
+
+    fn list(&self, filter: &DeviceFilter) -> SDRResult<Vec<Box<dyn DynDevice>>> {
+        Ok(self
+            .0
+            .list_with(filter)?
+            .into_iter()
+            .map(|d| Box::new(d) as Box<dyn DynDevice>)
+            .collect())
+    }
+
+    fn open(&self, args: &DeviceArgs) -> SDRResult<Box<dyn DynDevice>> {
+        Ok(Box::new(self.0.open(args)?))
+    }
+}
+
+#[derive(Default)]
+pub struct DriverRegistry {
+    drivers: Vec<Box<dyn DynDriver>>,
+}
+
+impl DriverRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every compiled-in driver for real hardware. The mock and file drivers
+    /// are left out even when enabled: the mock would show a fake loopback
+    /// device next to real radios, and the file driver needs a directory of
+    /// recordings. Add them with `register`, e.g.
+    /// `register(DriverFile::new(dir))`.
+    pub fn with_compiled_drivers() -> Self {
+        #[allow(unused_mut)]
+        let mut registry = Self::new();
+        #[cfg(feature = "driver-uhd")]
+        registry.register(starsdr_uhd::DriverUHD::new());
+        registry
+    }
+
+    pub fn register<D: SDRDriver + 'static>(&mut self, driver: D) -> &mut Self
+    where
+        D::Item: DynDevice + 'static,
+    {
+        self.register_dyn(Box::new(Driver(driver)))
+    }
+
+    pub fn register_dyn(&mut self, driver: Box<dyn DynDriver>) -> &mut Self {
+        self.drivers.push(driver);
+        self
+    }
+
+    pub fn driver_names(&self) -> Vec<&'static str> {
+        self.drivers.iter().map(|d| d.name()).collect()
+    }
+
+    pub fn list_all(&self) -> SDRResult<Vec<Box<dyn DynDevice>>> {
+        self.list_with(&DeviceFilter::new())
+    }
+
+    /// Devices matching `filter`. When the filter names a driver, that
+    /// driver's errors are returned. Across all drivers, a driver that fails
+    /// to list is logged and skipped, so one broken backend does not hide
+    /// the others.
+    pub fn list_with(&self, filter: &DeviceFilter) -> SDRResult<Vec<Box<dyn DynDevice>>> {
+        let devices = self.list_indexed(filter)?;
+        Ok(devices.into_iter().map(|(_, d)| d).collect())
+    }
+
+    /// Like `list_with`, with the index of the driver that found each device.
+    fn list_indexed(&self, filter: &DeviceFilter) -> SDRResult<Vec<(usize, Box<dyn DynDevice>)>> {
+        let name = filter.values.get("driver").map(|d| d.as_str());
+        let mut out = vec![];
+        for i in self.drivers_for(name)? {
+            let driver = &self.drivers[i];
+            match driver.list(filter) {
+                Ok(devices) => out.extend(devices.into_iter().map(|d| (i, d))),
+                Err(e) if name.is_some() => return Err(e),
+                Err(e) => warn!("{}: list devices: {}", driver.name(), e),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Opens a device from an argument string such as
+    /// `driver=uhd,serial=3150360`. Without `driver`, the first device any
+    /// driver lists for `args.filter()` is opened by its driver, with the
+    /// device's own fields added to `args`.
+    pub fn open(&self, args: &str) -> SDRResult<Box<dyn DynDevice>> {
+        self.open_args(&args.parse()?)
+    }
+
+    /// With several drivers of the same name, such as file drivers on
+    /// different directories, an explicit `driver` is tried on each in turn
+    /// and the last error is returned if none can open the device.
+    pub fn open_args(&self, args: &DeviceArgs) -> SDRResult<Box<dyn DynDevice>> {
+        if args.driver().is_some() {
+            let mut result = Err(SDRError::NotFound);
+            for i in self.drivers_for(args.driver())? {
+                result = self.drivers[i].open(args);
+                if result.is_ok() {
+                    break;
+                }
+            }
+            return result;
+        }
+        let (i, device) = self
+            .list_indexed(&args.filter())?
+            .into_iter()
+            .next()
+            .ok_or(SDRError::NotFound)?;
+        let info = device.info();
+        let mut args = args.clone().with("driver", &info.driver);
+        for (k, v) in info.values {
+            args.values.entry(k).or_insert(v);
+        }
+        self.drivers[i].open(&args)
+    }
+
+    /// Indices of the drivers called `name`, or of all drivers.
+    fn drivers_for(&self, name: Option<&str>) -> SDRResult<Vec<usize>> {
+        let drivers: Vec<_> = (0..self.drivers.len())
+            .filter(|&i| name.is_none_or(|n| self.drivers[i].name() == n))
+            .collect();
+        match name {
+            Some(n) if drivers.is_empty() => Err(SDRError::Param {
+                key: "driver".into(),
+                value: n.into(),
+                msg: format!("not registered, have {:?}", self.driver_names()),
+            }),
+            _ => Ok(drivers),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "driver-mock", feature = "driver-file"))]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use starsdr_file::{DeviceFile, DriverFile};
+    use starsdr_mock::{DeviceMock, DriverMock, MockConfig};
+
+    /// Registry with two mock devices, a file driver on a temporary directory
+    /// and one on its `more` subdirectory. The directory is removed on drop.
+    struct Fixture(DriverRegistry, PathBuf);
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.1);
+        }
+    }
+
+    fn registry(dir: &str) -> Fixture {
+        let dir =
+            std::env::temp_dir().join(format!("starsdr-registry-{}-{}", std::process::id(), dir));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.fc32"), []).unwrap();
+        std::fs::create_dir(dir.join("more")).unwrap();
+        std::fs::write(dir.join("more").join("x.fc32"), []).unwrap();
+
+        let mut r = DriverRegistry::new();
+        r.register(DriverMock::with_config(MockConfig {
+            devices: 2,
+            ..Default::default()
+        }))
+        .register(DriverFile::new(&dir))
+        .register(DriverFile::new(dir.join("more")));
+        Fixture(r, dir)
+    }
+
+    #[test]
+    fn test_list_all() {
+        let f = registry("list");
+        let r = &f.0;
+        assert_eq!(vec!["mock", "file", "file"], r.driver_names());
+
+        let devices = r.list_all().unwrap();
+        let drivers: Vec<_> = devices.iter().map(|d| d.driver_name()).collect();
+        assert_eq!(vec!["mock", "mock", "file", "file"], drivers);
+        assert!(devices[2].as_any().downcast_ref::<DeviceFile>().is_some());
+
+        let found = r.list_with(&DeviceFilter::new().serial("mock1")).unwrap();
+        assert_eq!(1, found.len());
+    }
+
+    #[test]
+    fn test_list_errors() {
+        let f = registry("errors");
+        let mut r = DriverRegistry::new();
+        r.register(DriverMock::new())
+            .register(DriverFile::new(f.1.join("missing")));
+
+        assert_eq!(1, r.list_all().unwrap().len());
+        let file = DeviceFilter::new().driver("file");
+        assert!(matches!(r.list_with(&file), Err(SDRError::Io(_))));
+        assert!(matches!(
+            r.open("driver=file,path=a.fc32"),
+            Err(SDRError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_open() {
+        let f = registry("open");
+        let r = &f.0;
+        let d = r.open("driver=mock,name=mock1").unwrap();
+        assert_eq!(Some("mock1".into()), d.serial());
+        assert!(d.as_any().downcast_ref::<DeviceMock>().is_some());
+
+        let tx = d.tx_stream_f64(&[0]).unwrap();
+        let mut rx = d.rx_stream_f64(&[0]).unwrap();
+        let data = vec![Complex::new(0.5f64, 0.25); 8];
+        tx.send(&data).unwrap();
+        assert_eq!(data, rx.recv().unwrap());
+
+        let d = r.open("path=b.sc16,driver=file").unwrap();
+        assert_eq!("file", d.driver_name());
+        assert_eq!("file", r.open("name=a").unwrap().driver_name());
+        let d = r.open("name=x").unwrap();
+        let file = d.as_any().downcast_ref::<DeviceFile>().unwrap();
+        assert!(file.path().ends_with("more/x.fc32"));
+        let d = r.open("name=mock0,master_clock_rate=200e6").unwrap();
+        assert_eq!(Some("mock0".into()), d.serial());
+
+        assert!(matches!(r.open("driver=nope"), Err(SDRError::Param { .. })));
+        assert!(matches!(r.open("name=missing"), Err(SDRError::NotFound)));
+    }
+}
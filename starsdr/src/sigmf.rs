@@ -0,0 +1,461 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use starsdr_interface::*;
+
+pub const SIGMF_VERSION: &str = "1.0.0";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Global {
+    #[serde(rename = "core:datatype")]
+    pub datatype: String,
+    #[serde(rename = "core:version")]
+    pub version: String,
+    #[serde(rename = "core:sample_rate", skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<f64>,
+    #[serde(rename = "core:hw", skip_serializing_if = "Option::is_none")]
+    pub hw: Option<String>,
+    #[serde(rename = "core:description", skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "core:author", skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(rename = "core:recorder", skip_serializing_if = "Option::is_none")]
+    pub recorder: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Capture {
+    #[serde(rename = "core:sample_start")]
+    pub sample_start: u64,
+    #[serde(rename = "core:frequency", skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<f64>,
+    #[serde(rename = "core:datetime", skip_serializing_if = "Option::is_none")]
+    pub datetime: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Annotation {
+    #[serde(rename = "core:sample_start")]
+    pub sample_start: u64,
+    #[serde(rename = "core:sample_count", skip_serializing_if = "Option::is_none")]
+    pub sample_count: Option<u64>,
+    #[serde(
+        rename = "core:freq_lower_edge",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub freq_lower_edge: Option<f64>,
+    #[serde(
+        rename = "core:freq_upper_edge",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub freq_upper_edge: Option<f64>,
+    #[serde(rename = "core:label", skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(rename = "core:comment", skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Meta {
+    pub global: Global,
+    #[serde(default)]
+    pub captures: Vec<Capture>,
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+}
+
+pub fn datatype(format: SampleFormat) -> &'static str {
+    match format {
+        SampleFormat::Sc16 => "ci16_le",
+        SampleFormat::Fc32 => "cf32_le",
+        SampleFormat::Fc64 => "cf64_le",
+    }
+}
+
+fn format_of(datatype: &str) -> SDRResult<SampleFormat> {
+    match datatype {
+        "ci16_le" => Ok(SampleFormat::Sc16),
+        "cf32_le" => Ok(SampleFormat::Fc32),
+        "cf64_le" => Ok(SampleFormat::Fc64),
+        _ => Err(SDRError::NotSupport(format!("sigmf datatype {}", datatype))),
+    }
+}
+
+/// `foo`, `foo.sigmf-meta` and `foo.sigmf-data` all name the recording `foo`.
+fn paths(path: &Path) -> (PathBuf, PathBuf) {
+    let base = match path.extension().and_then(|e| e.to_str()) {
+        Some("sigmf-meta") | Some("sigmf-data") => path.with_extension(""),
+        _ => path.to_path_buf(),
+    };
+    let mut data = base.clone().into_os_string();
+    data.push(".sigmf-data");
+    let mut meta = base.into_os_string();
+    meta.push(".sigmf-meta");
+    (data.into(), meta.into())
+}
+
+fn json_err(e: serde_json::Error) -> SDRError {
+    SDRError::Unknown(format!("sigmf meta: {}", e))
+}
+
+/// Writes a SigMF recording: samples go to `.sigmf-data` as they come, the
+/// metadata to `.sigmf-meta` on `finish()`. Dropping the writer without
+/// calling `finish()` leaves a data file without metadata. An existing
+/// recording is never overwritten.
+pub struct SigMFWriter<T: Sample> {
+    data: BufWriter<File>,
+    meta_path: PathBuf,
+    meta: Meta,
+    sample_count: u64,
+    buf: Vec<u8>,
+    _t: PhantomData<T>,
+}
+
+impl<T: Sample> SigMFWriter<T> {
+    /// Start a recording of `channel` on `device`, taking the sample rate,
+    /// center frequency and hardware description from the device.
+    pub fn create<D: SDRDevice>(
+        path: impl AsRef<Path>,
+        device: &D,
+        channel: usize,
+    ) -> SDRResult<Self> {
+        let mut w = Self::create_with(path, Meta::default())?;
+        w.meta.global.sample_rate = Some(device.get_rx_rate(channel)?);
+        w.meta.global.hw = Some(device.to_string());
+        w.meta.captures.push(Capture {
+            sample_start: 0,
+            frequency: Some(device.get_rx_freq(channel)?),
+            ..Default::default()
+        });
+        Ok(w)
+    }
+
+    pub fn create_with(path: impl AsRef<Path>, mut meta: Meta) -> SDRResult<Self> {
+        let (data_path, meta_path) = paths(path.as_ref());
+        meta.global.datatype = datatype(T::FORMAT).into();
+        meta.global.version = SIGMF_VERSION.into();
+        if meta.global.recorder.is_none() {
+            meta.global.recorder = Some(format!("starsdr {}", env!("CARGO_PKG_VERSION")));
+        }
+        let file = File::options()
+            .write(true)
+            .create_new(true)
+            .open(&data_path)
+            .map_err(|e| match e.kind() {
+                ErrorKind::AlreadyExists => SDRError::Param {
+                    key: "path".into(),
+                    value: data_path.display().to_string(),
+                    msg: "exists, refusing to overwrite a recording".into(),
+                },
+                _ => e.into(),
+            })?;
+        Ok(Self {
+            data: BufWriter::new(file),
+            meta_path,
+            meta,
+            sample_count: 0,
+            buf: vec![],
+            _t: PhantomData,
+        })
+    }
+
+    pub fn meta(&self) -> &Meta {
+        &self.meta
+    }
+
+    pub fn meta_mut(&mut self) -> &mut Meta {
+        &mut self.meta
+    }
+
+    pub fn sample_count(&self) -> u64 {
+        self.sample_count
+    }
+
+    pub fn add_capture(&mut self, capture: Capture) {
+        self.meta.captures.push(capture);
+    }
+
+    pub fn add_annotation(&mut self, annotation: Annotation) {
+        self.meta.annotations.push(annotation);
+    }
+
+    pub fn write(&mut self, v: &[Complex<T>]) -> SDRResult<()> {
+        self.buf.clear();
+        T::FORMAT.encode(v, &mut self.buf);
+        self.data.write_all(&self.buf)?;
+        self.sample_count += v.len() as u64;
+        Ok(())
+    }
+
+    /// Pull buffers from `rx` until `samples` samples are written.
+    /// Recording goes on through `TimeOut`, and through `Overflow` and out of
+    /// sequence packets, which start a new capture at the gap as SigMF asks
+    /// for discontinuities. Other errors end the recording.
+    pub fn record<R: Rx<T>>(&mut self, rx: &mut R, samples: u64) -> SDRResult<u64> {
+        let mut n = 0;
+        while n < samples {
+            let buf = match rx.recv_with_metadata() {
+                Ok((buf, md)) if md.out_of_sequence => {
+                    self.mark_gap();
+                    buf
+                }
+                Ok((buf, _)) => buf,
+                Err(SDRError::Overflow) => {
+                    self.mark_gap();
+                    continue;
+                }
+                Err(SDRError::TimeOut) => continue,
+                Err(e) => return Err(e),
+            };
+            let take = buf.len().min((samples - n) as usize);
+            self.write(&buf[..take])?;
+            n += take as u64;
+        }
+        Ok(n)
+    }
+
+    /// Starts a capture at the next sample, keeping the current frequency.
+    fn mark_gap(&mut self) {
+        let last = self.meta.captures.iter().max_by_key(|c| c.sample_start);
+        if last.is_some_and(|c| c.sample_start == self.sample_count) {
+            return;
+        }
+        let frequency = last.and_then(|c| c.frequency);
+        self.add_capture(Capture {
+            sample_start: self.sample_count,
+            frequency,
+            ..Default::default()
+        });
+    }
+
+    pub fn finish(mut self) -> SDRResult<Meta> {
+        self.data.flush()?;
+        self.meta.captures.sort_by_key(|c| c.sample_start);
+        self.meta.annotations.sort_by_key(|a| a.sample_start);
+        let json = serde_json::to_string_pretty(&self.meta).map_err(json_err)?;
+        std::fs::write(&self.meta_path, json)?;
+        Ok(self.meta)
+    }
+}
+
+/// A SigMF recording read back as an `Rx` source, converted to `T`.
+pub struct SigMFRx<T: Sample> {
+    reader: BufReader<File>,
+    format: SampleFormat,
+    meta: Meta,
+    pub sample_num_max: usize,
+    scratch: Vec<u8>,
+    position: u64,
+    _t: PhantomData<T>,
+}
+
+impl<T: Sample> SigMFRx<T> {
+    pub fn open(path: impl AsRef<Path>) -> SDRResult<Self> {
+        let (data_path, meta_path) = paths(path.as_ref());
+        let meta: Meta =
+            serde_json::from_reader(BufReader::new(File::open(meta_path)?)).map_err(json_err)?;
+        let format = format_of(&meta.global.datatype)?;
+        Ok(Self {
+            reader: BufReader::new(File::open(data_path)?),
+            format,
+            meta,
+            sample_num_max: SAMPLE_NUM_MAX,
+            scratch: vec![],
+            position: 0,
+            _t: PhantomData,
+        })
+    }
+
+    pub fn meta(&self) -> &Meta {
+        &self.meta
+    }
+}
+
+impl<T: Sample> Rx<T> for SigMFRx<T> {
+    fn sample_num_max(&self) -> usize {
+        self.sample_num_max
+    }
+
+    fn channel_count(&self) -> usize {
+        1
+    }
+
+    fn recv_multi_into(&mut self, bufs: &mut [&mut [Complex<T>]]) -> SDRResult<RecvInfo> {
+        check_channel_buffers(1, bufs.len())?;
+        let buf = &mut *bufs[0];
+        let len = buf.len().min(self.sample_num_max);
+        let n = self
+            .format
+            .read(&mut self.reader, &mut self.scratch, &mut buf[..len])?;
+        if n == 0 && len > 0 {
+            return Err(SDRError::EndOfStream);
+        }
+        let metadata = RxMetadata {
+            time_spec: self
+                .meta
+                .global
+                .sample_rate
+                .filter(|rate| *rate > 0.0)
+                .map(|rate| TimeSpec::from_samples(self.position, rate)),
+            ..Default::default()
+        };
+        self.position += n as u64;
+        Ok(RecvInfo {
+            samples: n,
+            metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starsdr_mock::{DriverMock, RxMock, TxMock};
+
+    /// Base path of a recording in the temp dir, whose files are removed on
+    /// drop.
+    struct TempRecording(PathBuf);
+
+    impl TempRecording {
+        fn new(name: &str) -> Self {
+            let base =
+                std::env::temp_dir().join(format!("starsdr-sigmf-{}-{}", std::process::id(), name));
+            let r = Self(base);
+            r.remove();
+            r
+        }
+
+        fn remove(&self) {
+            let (data, meta) = paths(&self.0);
+            let _ = std::fs::remove_file(data);
+            let _ = std::fs::remove_file(meta);
+        }
+    }
+
+    impl Drop for TempRecording {
+        fn drop(&mut self) {
+            self.remove();
+        }
+    }
+
+    #[test]
+    fn test_record_playback() {
+        let recording = TempRecording::new("playback");
+        let base = &recording.0;
+
+        let mut d = DriverMock::new().list().unwrap().pop().unwrap();
+        d.open().unwrap();
+        d.set_rx_rate(2e6, 0).unwrap();
+        d.set_rx_freq(433.92e6, 0).unwrap();
+        d.set_tx_freq(433.92e6, 0).unwrap();
+        let tx: TxMock<i16> = d.tx_stream(&[0]).unwrap();
+        let mut rx: RxMock<i16> = d.rx_stream(&[0]).unwrap();
+        let data: Vec<_> = (0..1000)
+            .map(|i| Complex::new(i as i16, -(i as i16)))
+            .collect();
+        tx.send(&data).unwrap();
+
+        let mut w = SigMFWriter::create(base, &d, 0).unwrap();
+        w.add_annotation(Annotation {
+            sample_start: 10,
+            sample_count: Some(20),
+            label: Some("burst".into()),
+            ..Default::default()
+        });
+        assert_eq!(1000, w.record(&mut rx, 1000).unwrap());
+        w.finish().unwrap();
+        assert!(matches!(
+            SigMFWriter::<i16>::create_with(base, Meta::default()),
+            Err(SDRError::Param { .. })
+        ));
+
+        let mut r: SigMFRx<i16> = SigMFRx::open(base.with_extension("sigmf-meta")).unwrap();
+        let meta = r.meta();
+        assert_eq!("ci16_le", meta.global.datatype);
+        assert_eq!(Some(2e6), meta.global.sample_rate);
+        assert_eq!(Some(d.to_string()), meta.global.hw);
+        assert_eq!(Some(433.92e6), meta.captures[0].frequency);
+        assert_eq!(Some("burst".into()), meta.annotations[0].label);
+        assert_eq!(data, r.recv().unwrap());
+        assert!(matches!(r.recv(), Err(SDRError::EndOfStream)));
+    }
+
+    /// Yields `len` samples per call, and an overflow every third call.
+    struct Gappy {
+        len: usize,
+        calls: usize,
+    }
+
+    impl Rx<f32> for Gappy {
+        fn channel_count(&self) -> usize {
+            1
+        }
+
+        fn sample_num_max(&self) -> usize {
+            self.len
+        }
+
+        fn recv_multi_into(&mut self, bufs: &mut [&mut [Complex<f32>]]) -> SDRResult<RecvInfo> {
+            self.calls += 1;
+            match self.calls % 3 {
+                0 => Err(SDRError::Overflow),
+                _ => Ok(RecvInfo {
+                    samples: self.len.min(bufs[0].len()),
+                    metadata: RxMetadata::default(),
+                }),
+            }
+        }
+    }
+
+    #[test]
+    fn test_record_gaps() {
+        let base = TempRecording::new("gaps");
+        let meta = Meta {
+            captures: vec![Capture {
+                sample_start: 0,
+                frequency: Some(1e9),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut w = SigMFWriter::<f32>::create_with(&base.0, meta).unwrap();
+        let mut rx = Gappy { len: 10, calls: 0 };
+        assert_eq!(45, w.record(&mut rx, 45).unwrap());
+
+        let meta = w.finish().unwrap();
+        let starts: Vec<_> = meta.captures.iter().map(|c| c.sample_start).collect();
+        assert_eq!(vec![0, 20, 40], starts);
+        assert!(meta.captures.iter().all(|c| c.frequency == Some(1e9)));
+    }
+
+    #[test]
+    fn test_meta_json() {
+        let json = r#"{
+            "global": {"core:datatype": "cf32_le", "core:version": "1.0.0", "my:ext": 1},
+            "captures": [{"core:sample_start": 0, "core:frequency": 1e9}],
+            "annotations": []
+        }"#;
+        let meta: Meta = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            Ok(SampleFormat::Fc32),
+            format_of(&meta.global.datatype).map_err(|_| ())
+        );
+        assert_eq!(Some(&Value::from(1)), meta.global.extra.get("my:ext"));
+
+        let out = serde_json::to_value(&meta).unwrap();
+        assert_eq!(1e9, out["captures"][0]["core:frequency"]);
+        assert!(out["global"].get("core:sample_rate").is_none());
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    thread,
+};
+
+use async_trait::async_trait;
+use futures::{channel::mpsc as futures_mpsc, executor, Sink, Stream, StreamExt};
+use starsdr_interface::*;
+use tokio::sync::mpsc;
+
+type RxItem<T> = SDRResult<Vec<Complex<T>>>;
+
+/// Runs a blocking `Rx` on its own thread and hands the buffers to async code
+/// through a channel holding at most `depth` buffers. When the channel is
+/// full the thread stops receiving, so a slow consumer shows up as device
+/// overflows instead of unbounded memory. A `depth` of 0 is taken as 1.
+///
+/// `TimeOut` and `Overflow` are passed on and streaming continues; any other
+/// error ends the stream.
+pub struct RxStream<T: Send> {
+    rx: mpsc::Receiver<RxItem<T>>,
+}
+
+impl<T: Sample> RxStream<T> {
+    pub fn new<R: Rx<T> + 'static>(mut rx: R, depth: usize) -> Self {
+        let (tx, rx_ch) = mpsc::channel(depth.max(1));
+        thread::spawn(move || loop {
+            let r = rx.recv();
+            let stop = matches!(&r, Err(e) if !matches!(e, SDRError::TimeOut | SDRError::Overflow));
+            if tx.blocking_send(r).is_err() || stop {
+                break;
+            }
+        });
+        Self { rx: rx_ch }
+    }
+}
+
+impl<T: Send> Stream for RxStream<T> {
+    type Item = RxItem<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+#[async_trait]
+impl<T: Send> AsyncRx<T> for RxStream<T> {
+    async fn recv(&mut self) -> SDRResult<Vec<Complex<T>>> {
+        self.rx.recv().await.unwrap_or(Err(SDRError::EndOfStream))
+    }
+}
+
+/// Runs a blocking `Tx` on its own thread, fed through a channel holding at
+/// most `depth` buffers, at least 1. `send` waits while the channel is full.
+/// Also a `futures::Sink`, so streams can be forwarded into it.
+///
+/// Errors from the device stop the thread and are returned by the next `send`.
+pub struct TxSink<T: Send> {
+    tx: futures_mpsc::Sender<Vec<Complex<T>>>,
+    error: Arc<Mutex<Option<SDRError>>>,
+}
+
+impl<T: Send + 'static> TxSink<T> {
+    pub fn new<S: Tx<T> + 'static>(tx: S, depth: usize) -> Self {
+        // The sender itself holds one more buffer than the channel's bound.
+        let (tx_ch, mut rx) = futures_mpsc::channel::<Vec<Complex<T>>>(depth.max(1) - 1);
+        let error = Arc::new(Mutex::new(None));
+        let thread_error = error.clone();
+        thread::spawn(move || {
+            while let Some(buf) = executor::block_on(rx.next()) {
+                if let Err(e) = tx.send(&buf) {
+                    *thread_error.lock().unwrap() = Some(e);
+                    break;
+                }
+            }
+        });
+        Self { tx: tx_ch, error }
+    }
+}
+
+impl<T: Send> TxSink<T> {
+    fn take_error(&self) -> SDRError {
+        self.error
+            .lock()
+            .unwrap()
+            .take()
+            .unwrap_or(SDRError::EndOfStream)
+    }
+}
+
+impl<T: Send> Sink<Vec<Complex<T>>> for TxSink<T> {
+    type Error = SDRError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<SDRResult<()>> {
+        self.tx.poll_ready(cx).map_err(|_| self.take_error())
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Vec<Complex<T>>) -> SDRResult<()> {
+        self.tx.start_send(item).map_err(|_| self.take_error())
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<SDRResult<()>> {
+        Pin::new(&mut self.tx)
+            .poll_flush(cx)
+            .map_err(|_| self.take_error())
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<SDRResult<()>> {
+        Pin::new(&mut self.tx)
+            .poll_close(cx)
+            .map_err(|_| self.take_error())
+    }
+}
+
+#[async_trait]
+impl<T: Send> AsyncTx<T> for TxSink<T> {
+    async fn send(&mut self, v: Vec<Complex<T>>) -> SDRResult<usize> {
+        let n = v.len();
+        futures::SinkExt::send(self, v).await?;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use futures::StreamExt;
+    use starsdr_mock::{DeviceMock, MockConfig, RxMock, TxMock};
+
+    fn device(config: MockConfig) -> DeviceMock {
+        let mut d = DeviceMock::new("mock0", config);
+        d.open().unwrap();
+        d
+    }
+
+    #[tokio::test]
+    async fn test_loopback() {
+        let d = device(MockConfig::default());
+        let tx: TxMock<f32> = d.tx_stream(&[0]).unwrap();
+        let rx: RxMock<f32> = d.rx_stream(&[0]).unwrap();
+        let mut sink = TxSink::new(tx, 0);
+        let mut stream = RxStream::new(rx, 0);
+
+        let data = vec![Complex::new(0.25f32, 0.5); 100];
+        assert_eq!(100, sink.send(data.clone()).await.unwrap());
+        assert_eq!(data, stream.next().await.unwrap().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_forward() {
+        let d = device(MockConfig::default());
+        let tx: TxMock<f32> = d.tx_stream(&[0]).unwrap();
+        let rx: RxMock<f32> = d.rx_stream(&[0]).unwrap();
+        let mut sink = TxSink::new(tx, 2);
+        let mut stream = RxStream::new(rx, 2);
+
+        let data = vec![Complex::new(0.25f32, 0.5); 100];
+        let bufs = vec![Ok(data[..60].to_vec()), Ok(data[60..].to_vec())];
+        futures::stream::iter(bufs)
+            .forward(&mut sink)
+            .await
+            .unwrap();
+
+        let mut got = vec![];
+        while got.len() < data.len() {
+            got.extend(stream.next().await.unwrap().unwrap());
+        }
+        assert_eq!(data, got);
+    }
+
+    #[tokio::test]
+    async fn test_select() {
+        let d = device(MockConfig {
+            timeout: Duration::from_millis(10),
+            ..Default::default()
+        });
+        let rx: RxMock<f32> = d.rx_stream(&[0]).unwrap();
+        let mut stream = RxStream::new(rx, 1);
+        let (ctrl_tx, mut ctrl_rx) = mpsc::channel::<()>(1);
+        ctrl_tx.send(()).await.unwrap();
+
+        tokio::select! {
+            _ = stream.next() => panic!("nothing was sent"),
+            r = ctrl_rx.recv() => assert!(r.is_some()),
+        }
+        assert!(matches!(stream.recv().await, Err(SDRError::TimeOut)));
+    }
+
+    #[tokio::test]
+    async fn test_tx_error() {
+        let d = device(MockConfig::default());
+        let tx: TxMock<f32> = d.tx_stream(&[0]).unwrap();
+        let mut sink = TxSink::new(tx, 1);
+
+        sink.send(vec![Complex::default(); 10_000]).await.unwrap();
+        // The thread closes the channel once the device rejects the buffer.
+        let r = loop {
+            if let Err(e) = sink.send(vec![]).await {
+                break e;
+            }
+        };
+        assert!(matches!(r, SDRError::Param { .. }));
+    }
+}
@@ -0,0 +1,120 @@
+use log::warn;
+use starsdr_interface::*;
+
+/// Clears the command time of `mboard` when dropped.
+pub struct CommandTimeGuard<'a, D: CommandTime + ?Sized> {
+    device: &'a D,
+    mboard: usize,
+}
+
+impl<'a, D: CommandTime + ?Sized> CommandTimeGuard<'a, D> {
+    pub fn new(device: &'a D, time: TimeSpec, mboard: usize) -> SDRResult<Self> {
+        device.set_command_time(time, mboard)?;
+        Ok(Self { device, mboard })
+    }
+}
+
+impl<D: CommandTime + ?Sized> Drop for CommandTimeGuard<'_, D> {
+    fn drop(&mut self) {
+        if let Err(e) = self.device.clear_command_time(self.mboard) {
+            warn!("clear command time: {}", e);
+        }
+    }
+}
+
+pub trait AtTime: CommandTime {
+    /// Runs `f` with the command time set to `time` on all boards, so every
+    /// control call inside takes effect at that device time. The command
+    /// time is cleared afterwards, also when `f` fails or panics. Errors from
+    /// setting the command time and from `f` are returned alike.
+    ///
+    /// ```no_run
+    /// # use starsdr::*;
+    /// # fn hop<D: SDRDevice + CommandTime + DeviceTime>(d: &D) -> SDRResult<()> {
+    /// let t = d.time_now(0)? + TimeSpec::from_secs(0.1);
+    /// d.at_time(t, |d| {
+    ///     d.set_rx_freq(915e6, 0)?;
+    ///     d.set_rx_freq(915e6, 1)
+    /// })
+    /// # }
+    /// ```
+    fn at_time<R, F: FnOnce(&Self) -> SDRResult<R>>(&self, time: TimeSpec, f: F) -> SDRResult<R> {
+        let _guard = CommandTimeGuard::new(self, time, ALL_MBOARDS)?;
+        f(self)
+    }
+}
+
+impl<D: CommandTime + ?Sized> AtTime for D {}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, panic};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        log: RefCell<Vec<String>>,
+    }
+
+    impl Recorder {
+        fn tune(&self, freq: f64) -> SDRResult<()> {
+            if freq < 0.0 {
+                return Err(SDRError::Param {
+                    key: "freq".into(),
+                    value: freq.to_string(),
+                    msg: "negative".into(),
+                });
+            }
+            self.log.borrow_mut().push(format!("tune {}", freq));
+            Ok(())
+        }
+    }
+
+    impl CommandTime for Recorder {
+        fn set_command_time(&self, time: TimeSpec, _mboard: usize) -> SDRResult<()> {
+            self.log.borrow_mut().push(format!("set {}", time));
+            Ok(())
+        }
+
+        fn clear_command_time(&self, _mboard: usize) -> SDRResult<()> {
+            self.log.borrow_mut().push("clear".into());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_at_time() {
+        let d = Recorder::default();
+        let t = TimeSpec::new(2, 0.5);
+        d.at_time(t, |d| {
+            d.tune(1.0)?;
+            d.tune(2.0)
+        })
+        .unwrap();
+        assert_eq!(
+            vec![
+                format!("set {}", t),
+                "tune 1".into(),
+                "tune 2".into(),
+                "clear".into()
+            ],
+            *d.log.borrow()
+        );
+    }
+
+    #[test]
+    fn test_clear_on_error() {
+        let d = Recorder::default();
+        let r = d.at_time(TimeSpec::default(), |d| d.tune(-1.0));
+        assert!(matches!(r, Err(SDRError::Param { .. })));
+        assert_eq!("clear", d.log.borrow().last().unwrap());
+
+        let d = Recorder::default();
+        let r = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            d.at_time(TimeSpec::default(), |_| -> SDRResult<()> { panic!("boom") })
+        }));
+        assert!(r.is_err());
+        assert_eq!("clear", d.log.borrow().last().unwrap());
+    }
+}